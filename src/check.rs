@@ -0,0 +1,48 @@
+//! Extension point for user defined validation rules.
+use crate::error::ValidationErrors;
+use sounding_base::Sounding;
+
+/// A validation rule that can be registered with a `Validator`.
+///
+/// Any function or closure with the signature `Fn(&Sounding, &mut ValidationErrors)` is also a
+/// `Check`.
+///
+/// # Examples
+///
+/// ```rust
+/// use sounding_base::Sounding;
+/// use sounding_validate::{Check, ValidationError, ValidationErrors, Validator};
+///
+/// struct RequireStationNumber;
+///
+/// impl Check for RequireStationNumber {
+///     fn check(&self, snd: &Sounding, errors: &mut ValidationErrors) {
+///         if snd.station_info().station_num().is_none() {
+///             errors.push_error(Err(ValidationError::Custom(
+///                 "STN_NUM",
+///                 None,
+///                 "missing station number".to_owned(),
+///             )));
+///         }
+///     }
+/// }
+///
+/// let validator = Validator::new()
+///     .with_pressure_profile_check(false)
+///     .with_check(RequireStationNumber);
+///
+/// assert!(validator.validate(&Sounding::new()).is_err());
+/// ```
+pub trait Check {
+    /// Check the sounding and add any errors found to `errors`.
+    fn check(&self, snd: &Sounding, errors: &mut ValidationErrors);
+}
+
+impl<F> Check for F
+where
+    F: Fn(&Sounding, &mut ValidationErrors),
+{
+    fn check(&self, snd: &Sounding, errors: &mut ValidationErrors) {
+        self(snd, errors)
+    }
+}
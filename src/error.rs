@@ -1,9 +1,64 @@
 //! Errors for the sounding-validate crate.
+use chrono::NaiveDateTime;
+use metfor::{HectoPascal, Meters};
+use optional::Optioned;
+use sounding_base::Sounding;
 use std::error::Error;
 use std::fmt;
 
+/// The location of a level in the sounding that failed a check.
+#[derive(Clone, Copy, Debug)]
+pub struct Level {
+    /// Index into the profiles of the sounding. Index 0 is the surface.
+    pub index: usize,
+    /// The pressure at this level, if it was available.
+    pub pressure: Optioned<HectoPascal>,
+    /// The geopotential height of this level, if it was available.
+    pub height: Optioned<Meters>,
+}
+
+impl Level {
+    /// Gather the information describing the level at `index` of the sounding.
+    pub fn from_sounding(snd: &Sounding, index: usize) -> Self {
+        let pressure = snd
+            .pressure_profile()
+            .get(index)
+            .cloned()
+            .unwrap_or_default();
+        let height = snd.height_profile().get(index).cloned().unwrap_or_default();
+
+        Level {
+            index,
+            pressure,
+            height,
+        }
+    }
+}
+
+// Missing values are NaN inside an Optioned, so compare the options instead.
+impl PartialEq for Level {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index
+            && self.pressure.into_option() == other.pressure.into_option()
+            && self.height.into_option() == other.height.into_option()
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "level {}", self.index)?;
+
+        match (self.pressure.into_option(), self.height.into_option()) {
+            (Some(HectoPascal(p)), Some(Meters(h))) => write!(f, " ({} hPa, {} m)", p, h),
+            (Some(HectoPascal(p)), None) => write!(f, " ({} hPa)", p),
+            (None, Some(Meters(h))) => write!(f, " ({} m)", h),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
 /// Validation errors.
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum ValidationError {
     /// Sounding did not have a profile for pressure.
     NoPressureProfile,
@@ -12,20 +67,123 @@ pub enum ValidationError {
     /// the name of the profile, the first `usize` is the length of that profile, and the second
     /// `usize` is the length it should have been.
     InvalidVectorLength(&'static str, usize, usize),
-    /// Pressure not decreasing with height. This also checks that geopotential increases
-    /// "with height". It assumes the vectors are sorted with values from the lowest level to the
-    /// highest level above ground.
-    PressureNotDecreasingWithHeight,
+    /// Pressure not decreasing with height. It assumes the vectors are sorted with values from
+    /// the lowest level to the highest level above ground. The level is the one found out of
+    /// order, the first value is the pressure at the next valid level below it and the second
+    /// value is the pressure at the level itself.
+    PressureNotDecreasingWithHeight(Level, f64, f64),
+    /// Geopotential height not increasing with height. The level is the one found out of order,
+    /// the first value is the height of the next valid level below it and the second value is
+    /// the height of the level itself.
+    HeightNotIncreasingWithHeight(Level, f64, f64),
+    /// The pressure difference between two consecutive valid levels is too large. The value is
+    /// the size of the gap.
+    PressureGap(Level, Level, f64),
+    /// The height difference between two consecutive valid levels is too large. The value is the
+    /// size of the gap.
+    HeightGap(Level, Level, f64),
+    /// The sounding has too few levels to be useful. The values are the number of levels above
+    /// the surface and the minimum required.
+    TooFewLevels(usize, usize),
+    /// Too many levels of a profile are missing in a layer. The string names the profile, and
+    /// the values are the fraction missing and the maximum allowed.
+    TooMuchMissingData(&'static str, f64, f64),
+    /// A profile has too deep a gap in its data in a layer. The string names the profile, and the
+    /// values are the depth of the gap in hPa and the maximum allowed.
+    MissingDataGap(&'static str, f64, f64),
+    /// The sounding does not reach the required top pressure or height with temperature data,
+    /// as when a balloon bursts early. The level is the highest one with a temperature, if any.
+    EarlyTermination(Option<Level>),
+    /// A required pressure level does not have a temperature and height. The value is the
+    /// pressure of the level.
+    MissingMandatoryLevel(f64),
+    /// Two consecutive levels have the same pressure, but the data available at both levels
+    /// agrees.
+    DuplicateLevel(Level, Level),
+    /// Two consecutive levels have the same pressure, and the data at the levels differs.
+    ConflictingDuplicateLevel(Level, Level),
+    /// The station pressure is less than the pressure of the lowest level above the surface. The
+    /// first value is the station pressure and the second the pressure at that level.
+    StationPressureLessThanFirstLevel(Level, f64, f64),
+    /// The station elevation is above the height of the lowest level above the surface. The first
+    /// value is the station elevation and the second is the height of that level.
+    StationElevationAboveFirstHeight(Level, f64, f64),
+    /// The station elevation is too far below the height of the lowest level above the surface.
+    /// The first value is the station elevation and the second the height at that level.
+    StationElevationMismatch(Level, f64, f64),
     /// Checks the required relationship between temperature and wet bulb.
-    TemperatureLessThanWetBulb(f64, f64),
+    TemperatureLessThanWetBulb(Level, f64, f64),
     /// Checks the required relationship between temperature and dew point.
-    TemperatureLessThanDewPoint(f64, f64),
+    TemperatureLessThanDewPoint(Level, f64, f64),
     /// Checks the required relationship between wet bulb and dew point.
-    WetBulbLessThanDewPoint(f64, f64),
-    /// Invalid negative value, such as speed which must be positive.
-    InvalidNegativeValue(&'static str, f64),
-    /// Invalid wind direction.
-    InvalidWindDirection(f64),
+    WetBulbLessThanDewPoint(Level, f64, f64),
+    /// Checks the required relationship between the surface temperature and dew point.
+    SurfaceTemperatureLessThanDewPoint(f64, f64),
+    /// Invalid negative value, such as speed which must be positive. The level is `None` for
+    /// values that do not belong to a level, such as the MSLP.
+    InvalidNegativeValue(&'static str, Option<Level>, f64),
+    /// Invalid wind direction. The level is `None` for the surface wind.
+    InvalidWindDirection(Option<Level>, f64),
+    /// A calm wind with a direction other than 0. The level is `None` for the surface wind when
+    /// there is no wind profile, and the value is the direction.
+    CalmWindWithDirection(Option<Level>, f64),
+    /// A wind with a speed and direction 0, which is reserved for calm winds. The value is the
+    /// speed.
+    WindSpeedWithoutDirection(Option<Level>, f64),
+    /// A wind direction of 360 when north winds should be 0. The value is the direction.
+    UnnormalizedWindDirection(Option<Level>, f64),
+    /// The wind speed at a level is much faster than the levels above and below it. The values
+    /// are the speed at the level, the speed below, and the speed above.
+    WindSpeedSpike(Level, f64, f64, f64),
+    /// The magnitude of the vector wind shear between two levels is implausible. The value is
+    /// the shear in knots per km.
+    ExcessiveWindShear(Level, Level, f64),
+    /// The thickness of a layer computed from the pressure and (virtual) temperature with the
+    /// hypsometric equation does not match the difference in the reported heights. The levels
+    /// are the bottom and top of the layer, the first value is the computed thickness and the
+    /// second the reported thickness.
+    HydrostaticInconsistency(Level, Level, f64, f64),
+    /// The stored equivalent potential temperature does not match the value computed from the
+    /// pressure, temperature, and dew point. The first value is the stored value and the second
+    /// the computed value, both in Kelvin.
+    ThetaEMismatch(Level, f64, f64),
+    /// The stored wet bulb temperature does not match the value computed from the pressure,
+    /// temperature, and dew point. The first value is the stored value and the second the
+    /// computed value, both in Celsius.
+    WetBulbMismatch(Level, f64, f64),
+    /// The potential temperature decreases with height through a layer. The levels are the
+    /// bottom and top of the layer, and the values are the potential temperature at the bottom
+    /// and top of the layer in Kelvin.
+    SuperadiabaticLapseRate(Level, Level, f64, f64),
+    /// A value is outside the plausible range for its pressure level. The string is the name of
+    /// the variable, the first value is the value and the second is the limit it broke.
+    GrossLimitExceeded(&'static str, Level, f64, f64),
+    /// The mean sea level pressure is less than the station pressure for a station above sea
+    /// level. The values are the MSLP and the station pressure.
+    MslpLessThanStationPressure(f64, f64),
+    /// The mean sea level pressure does not match the station pressure reduced to sea level. The
+    /// values are the reported and the computed MSLP.
+    MslpMismatch(f64, f64),
+    /// The valid time is outside the expected window. The values are the valid time and the
+    /// earliest and latest times allowed.
+    ValidTimeOutsideWindow(NaiveDateTime, NaiveDateTime, NaiveDateTime),
+    /// The values of a variable look like they were stored with the wrong units. The first string
+    /// names the variable and the second is the suspected unit.
+    SuspectedUnitError(&'static str, &'static str),
+    /// A value that is infinite, or NaN where NaN does not mean missing. The string names the
+    /// variable and the level is `None` for values that are not part of a profile.
+    NonFiniteValue(&'static str, Option<Level>, f64),
+    /// A value outside its valid range. The string names the variable, the level is `None` for
+    /// values that are not part of a profile, and the values are the value, minimum, and maximum.
+    OutOfRange(&'static str, Option<Level>, f64, f64, f64),
+    /// A surface value does not agree with the lowest level or levels of the profile. The string
+    /// names the variable, the level is the lowest level compared, and the values are the
+    /// surface value and the value at that level.
+    SurfaceProfileMismatch(&'static str, Level, f64, f64),
+    /// An error found by a user defined `Check`. The string is an id for the rule that failed,
+    /// the level is `None` if the rule does not apply to a single level, and the last value is a
+    /// message describing the failure.
+    Custom(&'static str, Option<Level>, String),
 }
 
 impl fmt::Display for ValidationError {
@@ -35,24 +193,215 @@ impl fmt::Display for ValidationError {
         match self {
             NoPressureProfile => write!(f, "missing pressure profile"),
             InvalidVectorLength(_, _, _) => write!(f, "vectors do not match length"),
-            PressureNotDecreasingWithHeight => write!(f, "pressure not decreasing with height"),
-            TemperatureLessThanWetBulb(_, _) => write!(f, "temperature less than wet bulb"),
-            TemperatureLessThanDewPoint(_, _) => write!(f, "temperature less than dew point"),
-            WetBulbLessThanDewPoint(_, _) => write!(f, "wet bulb less than dew point"),
-            InvalidNegativeValue(msg, val) => {
+            PressureNotDecreasingWithHeight(lvl, below, val) => write!(
+                f,
+                "pressure not decreasing with height at {}: {} below, {} at level",
+                lvl, below, val
+            ),
+            HeightNotIncreasingWithHeight(lvl, below, val) => write!(
+                f,
+                "height not increasing with height at {}: {} below, {} at level",
+                lvl, below, val
+            ),
+            PressureGap(lvl_a, lvl_b, gap) => {
+                write!(f, "pressure gap from {} to {}: {} hPa", lvl_a, lvl_b, gap)
+            }
+            HeightGap(lvl_a, lvl_b, gap) => {
+                write!(f, "height gap from {} to {}: {} m", lvl_a, lvl_b, gap)
+            }
+            TooFewLevels(num, min) => write!(f, "too few levels: {} < {}", num, min),
+            TooMuchMissingData(name, frac, max) => write!(
+                f,
+                "{} has too much missing data: {:.0}% > {:.0}%",
+                name,
+                frac * 100.0,
+                max * 100.0
+            ),
+            MissingDataGap(name, gap, max) => write!(
+                f,
+                "{} has a gap in the data: {} hPa > {} hPa",
+                name, gap, max
+            ),
+            EarlyTermination(Some(lvl)) => write!(f, "sounding terminated early at {}", lvl),
+            EarlyTermination(None) => write!(f, "sounding terminated early, no temperature data"),
+            MissingMandatoryLevel(p) => write!(f, "missing mandatory level: {} hPa", p),
+            DuplicateLevel(lvl_a, lvl_b) => {
+                write!(f, "duplicate level: {} repeats {}", lvl_b, lvl_a)
+            }
+            ConflictingDuplicateLevel(lvl_a, lvl_b) => write!(
+                f,
+                "conflicting duplicate level: {} repeats {} with different data",
+                lvl_b, lvl_a
+            ),
+            StationPressureLessThanFirstLevel(lvl, stn, val) => write!(
+                f,
+                "station pressure less than pressure at {}: {} < {}",
+                lvl, stn, val
+            ),
+            StationElevationAboveFirstHeight(lvl, elev, val) => write!(
+                f,
+                "station elevation above height at {}: {} > {}",
+                lvl, elev, val
+            ),
+            StationElevationMismatch(lvl, elev, val) => write!(
+                f,
+                "station elevation too far below {}: {} < {}",
+                lvl, elev, val
+            ),
+            TemperatureLessThanWetBulb(lvl, t, wb) => write!(
+                f,
+                "temperature less than wet bulb at {}: {} < {}",
+                lvl, t, wb
+            ),
+            TemperatureLessThanDewPoint(lvl, t, dp) => write!(
+                f,
+                "temperature less than dew point at {}: {} < {}",
+                lvl, t, dp
+            ),
+            WetBulbLessThanDewPoint(lvl, wb, dp) => write!(
+                f,
+                "wet bulb less than dew point at {}: {} < {}",
+                lvl, wb, dp
+            ),
+            SurfaceTemperatureLessThanDewPoint(t, dp) => {
+                write!(f, "surface temperature less than dew point: {} < {}", t, dp)
+            }
+            InvalidNegativeValue(msg, Some(lvl), val) => {
+                write!(f, "invalid negative value at {}: {} : {}", lvl, msg, val)
+            }
+            InvalidNegativeValue(msg, None, val) => {
                 write!(f, "invalid negative value: {} : {}", msg, val)
             }
-            InvalidWindDirection(dir) => write!(f, "invalid wind direction: {}", dir),
+            InvalidWindDirection(Some(lvl), dir) => {
+                write!(f, "invalid wind direction at {}: {}", lvl, dir)
+            }
+            InvalidWindDirection(None, dir) => write!(f, "invalid wind direction: {}", dir),
+            CalmWindWithDirection(Some(lvl), dir) => {
+                write!(f, "calm wind with a direction at {}: {}", lvl, dir)
+            }
+            CalmWindWithDirection(None, dir) => write!(f, "calm wind with a direction: {}", dir),
+            WindSpeedWithoutDirection(Some(lvl), spd) => {
+                write!(f, "wind speed without a direction at {}: {}", lvl, spd)
+            }
+            WindSpeedWithoutDirection(None, spd) => {
+                write!(f, "wind speed without a direction: {}", spd)
+            }
+            UnnormalizedWindDirection(Some(lvl), dir) => {
+                write!(f, "wind direction not normalized at {}: {}", lvl, dir)
+            }
+            UnnormalizedWindDirection(None, dir) => {
+                write!(f, "wind direction not normalized: {}", dir)
+            }
+            WindSpeedSpike(lvl, spd, below, above) => write!(
+                f,
+                "wind speed spike at {}: {} with {} below and {} above",
+                lvl, spd, below, above
+            ),
+            ExcessiveWindShear(bottom, top, shear) => write!(
+                f,
+                "excessive wind shear from {} to {}: {} knots per km",
+                bottom, top, shear
+            ),
+            HydrostaticInconsistency(bottom, top, computed, reported) => write!(
+                f,
+                "hydrostatic inconsistency from {} to {}: computed thickness {}, reported {}",
+                bottom, top, computed, reported
+            ),
+            ThetaEMismatch(lvl, stored, computed) => write!(
+                f,
+                "theta-e does not match computed value at {}: stored {}, computed {}",
+                lvl, stored, computed
+            ),
+            WetBulbMismatch(lvl, stored, computed) => write!(
+                f,
+                "wet bulb does not match computed value at {}: stored {}, computed {}",
+                lvl, stored, computed
+            ),
+            SuperadiabaticLapseRate(bottom, top, theta_bottom, theta_top) => write!(
+                f,
+                "superadiabatic lapse rate from {} to {}: theta {} to {}",
+                bottom, top, theta_bottom, theta_top
+            ),
+            GrossLimitExceeded(name, lvl, val, bound) => write!(
+                f,
+                "{} outside gross limits at {}: {} beyond limit {}",
+                name, lvl, val, bound
+            ),
+            MslpLessThanStationPressure(mslp, stn) => write!(
+                f,
+                "MSLP less than station pressure above sea level: {} < {}",
+                mslp, stn
+            ),
+            MslpMismatch(mslp, computed) => write!(
+                f,
+                "MSLP does not match reduced station pressure: {} != {}",
+                mslp, computed
+            ),
+            ValidTimeOutsideWindow(vt, start, end) => write!(
+                f,
+                "valid time outside expected window: {} not in {} to {}",
+                vt, start, end
+            ),
+            SuspectedUnitError(name, unit) => {
+                write!(f, "{} looks like it is in the wrong units: {}", name, unit)
+            }
+            NonFiniteValue(name, Some(lvl), val) => {
+                write!(f, "{} not finite at {}: {}", name, lvl, val)
+            }
+            NonFiniteValue(name, None, val) => write!(f, "{} not finite: {}", name, val),
+            OutOfRange(name, Some(lvl), val, min, max) => write!(
+                f,
+                "{} out of range at {}: {} not in {} to {}",
+                name, lvl, val, min, max
+            ),
+            OutOfRange(name, None, val, min, max) => write!(
+                f,
+                "{} out of range: {} not in {} to {}",
+                name, val, min, max
+            ),
+            SurfaceProfileMismatch(name, lvl, sfc, val) => write!(
+                f,
+                "surface {} does not match profile at {}: {} != {}",
+                name.to_lowercase(),
+                lvl,
+                sfc,
+                val
+            ),
+            Custom(id, Some(lvl), msg) => write!(f, "{} at {}: {}", id, lvl, msg),
+            Custom(id, None, msg) => write!(f, "{}: {}", id, msg),
         }
     }
 }
 
 impl Error for ValidationError {}
 
+/// How serious a validation finding is.
+///
+/// Severities are ordered, so `Severity::Info < Severity::Warning < Severity::Error`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// Informational, the data is probably fine but may be worth a look.
+    Info,
+    /// Suspicious data that does not make the sounding unusable.
+    Warning,
+    /// Invalid data.
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Info => write!(f, "info"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
 /// Collection of validation errors.
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 pub struct ValidationErrors {
-    errors: Vec<ValidationError>,
+    errors: Vec<(Severity, ValidationError)>,
 }
 
 impl ValidationErrors {
@@ -61,19 +410,54 @@ impl ValidationErrors {
         ValidationErrors { errors: vec![] }
     }
 
-    /// Get the interior list of errors.
+    /// Get the interior list of errors, regardless of severity.
     pub fn into_inner(self) -> Vec<ValidationError> {
+        self.errors.into_iter().map(|(_, err)| err).collect()
+    }
+
+    /// Get the interior list of errors along with their severity.
+    pub fn into_inner_with_severity(self) -> Vec<(Severity, ValidationError)> {
         self.errors
     }
 
+    /// Iterate over the errors along with their severity.
+    pub fn iter(&self) -> impl Iterator<Item = (Severity, &ValidationError)> {
+        self.errors.iter().map(|(sev, err)| (*sev, err))
+    }
+
+    /// Get the highest severity in this list, or `None` if it is empty.
+    pub fn max_severity(&self) -> Option<Severity> {
+        self.errors.iter().map(|(sev, _)| *sev).max()
+    }
+
     /// Add an error to this list
     pub fn push_error(&mut self, result: Result<(), ValidationError>) {
+        self.push_with_severity(result, Severity::Error);
+    }
+
+    /// Add an error to this list as a warning.
+    pub fn push_warning(&mut self, result: Result<(), ValidationError>) {
+        self.push_with_severity(result, Severity::Warning);
+    }
+
+    /// Add an error to this list as information.
+    pub fn push_info(&mut self, result: Result<(), ValidationError>) {
+        self.push_with_severity(result, Severity::Info);
+    }
+
+    /// Add an error to this list with the given severity.
+    pub fn push_with_severity(&mut self, result: Result<(), ValidationError>, severity: Severity) {
         match result {
             Ok(()) => {}
-            Err(err) => self.errors.push(err),
+            Err(err) => self.errors.push((severity, err)),
         }
     }
 
+    /// Move all the errors from `other` into this list, keeping their severity.
+    pub fn append(&mut self, mut other: ValidationErrors) {
+        self.errors.append(&mut other.errors);
+    }
+
     /// Check if there are any errors, if not return `Ok`, otherwise return `self`.
     pub fn check_any(self) -> Result<(), ValidationErrors> {
         if self.errors.is_empty() {
@@ -82,13 +466,23 @@ impl ValidationErrors {
             Err(self)
         }
     }
+
+    /// Check if there are any errors with a severity of at least `threshold`, if not return `Ok`,
+    /// otherwise return `self`. The returned list still contains the less severe errors.
+    pub fn check_severity(self, threshold: Severity) -> Result<(), ValidationErrors> {
+        if self.errors.iter().all(|(sev, _)| *sev < threshold) {
+            Ok(())
+        } else {
+            Err(self)
+        }
+    }
 }
 
 impl fmt::Display for ValidationErrors {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(f, "\nValidation Errors")?;
-        for error in &self.errors {
-            writeln!(f, "     {}", error)?;
+        for (severity, error) in &self.errors {
+            writeln!(f, "     {}: {}", severity, error)?;
         }
 
         writeln!(f)
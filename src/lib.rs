@@ -3,6 +3,9 @@
 /*! Provides validation facilities for the
 [sounding-base](https://github.com/rnleach/sounding-base.git) crate.
 
+Use `validate` to run the default set of checks, or configure a `Validator` to choose which
+checks run and their tolerances.
+
 See [examples](examples/validate.rs) for example of library use.
 
 */
@@ -10,12 +13,22 @@ See [examples](examples/validate.rs) for example of library use.
 //
 // API
 //
-pub use crate::error::{ValidationError, ValidationErrors};
-pub use crate::validate::validate;
+pub use crate::check::Check;
+pub use crate::error::{Level, Severity, ValidationError, ValidationErrors};
+pub use crate::limits::{GrossLimit, GrossLimits};
+pub use crate::missing::{missing_data_stats, MissingDataStats, MissingDataThreshold, Profile};
+pub use crate::validate::{normalize_north_winds, validate};
+pub use crate::validator::{CloudScale, Validator};
 
 //
 // Internal use only
 //
 
+mod check;
 mod error;
+mod limits;
+mod missing;
+mod thermo;
+mod units;
 mod validate;
+mod validator;
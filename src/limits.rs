@@ -0,0 +1,179 @@
+//! Climatological gross limit checks.
+use crate::error::*;
+use metfor::{Celsius, HectoPascal, Knots, WindSpdDir};
+use sounding_base::Sounding;
+
+/// Plausible values at a pressure level, one row of a `GrossLimits` table.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GrossLimit {
+    /// The pressure level these limits apply to.
+    pub pressure: HectoPascal,
+    /// The minimum plausible temperature.
+    pub min_temperature: Celsius,
+    /// The maximum plausible temperature.
+    pub max_temperature: Celsius,
+    /// The minimum plausible dew point.
+    pub min_dew_point: Celsius,
+    /// The maximum plausible dew point.
+    pub max_dew_point: Celsius,
+    /// The maximum plausible wind speed.
+    pub max_wind_speed: Knots,
+}
+
+impl GrossLimit {
+    /// Create a row for the table.
+    pub fn new<P>(
+        pressure: P,
+        temperature: (Celsius, Celsius),
+        dew_point: (Celsius, Celsius),
+        max_wind_speed: Knots,
+    ) -> Self
+    where
+        HectoPascal: From<P>,
+    {
+        GrossLimit {
+            pressure: HectoPascal::from(pressure),
+            min_temperature: temperature.0,
+            max_temperature: temperature.1,
+            min_dew_point: dew_point.0,
+            max_dew_point: dew_point.1,
+            max_wind_speed,
+        }
+    }
+}
+
+/// A table of plausible minimum and maximum values by pressure level.
+///
+/// Limits between the levels in the table are interpolated linearly in the log of pressure, and
+/// levels above or below the table use the limits of the nearest row. The default table is
+/// meant to only catch gross errors anywhere on the globe.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GrossLimits {
+    // Sorted from highest to lowest pressure.
+    rows: Vec<GrossLimit>,
+}
+
+impl Default for GrossLimits {
+    fn default() -> Self {
+        let row = |p, t: (f64, f64), dp: (f64, f64), wspd| {
+            GrossLimit::new(
+                HectoPascal(p),
+                (Celsius(t.0), Celsius(t.1)),
+                (Celsius(dp.0), Celsius(dp.1)),
+                Knots(wspd),
+            )
+        };
+
+        // The cold limits near the surface allow for the Antarctic plateau in winter.
+        GrossLimits::new(vec![
+            row(1050.0, (-75.0, 60.0), (-90.0, 35.0), 150.0),
+            row(850.0, (-90.0, 45.0), (-100.0, 30.0), 175.0),
+            row(700.0, (-90.0, 30.0), (-100.0, 25.0), 200.0),
+            row(500.0, (-90.0, 10.0), (-105.0, 5.0), 250.0),
+            row(300.0, (-75.0, -5.0), (-110.0, -10.0), 300.0),
+            row(200.0, (-85.0, -25.0), (-120.0, -30.0), 300.0),
+            row(100.0, (-100.0, -30.0), (-120.0, -40.0), 250.0),
+            row(50.0, (-100.0, -20.0), (-120.0, -40.0), 250.0),
+            row(10.0, (-100.0, 0.0), (-120.0, -40.0), 300.0),
+        ])
+    }
+}
+
+impl GrossLimits {
+    /// Create a table from its rows, which may be in any order.
+    pub fn new(mut rows: Vec<GrossLimit>) -> Self {
+        rows.sort_by(|a, b| {
+            b.pressure
+                .0
+                .partial_cmp(&a.pressure.0)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+
+        GrossLimits { rows }
+    }
+
+    /// Get the limits at a pressure level, `None` if the table is empty.
+    pub fn limits_at<P>(&self, pressure: P) -> Option<GrossLimit>
+    where
+        HectoPascal: From<P>,
+    {
+        let pressure = HectoPascal::from(pressure);
+        let HectoPascal(p) = pressure;
+
+        let first = *self.rows.first()?;
+        let last = *self.rows.last()?;
+        if p >= first.pressure.0 {
+            return Some(first);
+        }
+        if p <= last.pressure.0 {
+            return Some(last);
+        }
+
+        let (below, above) = self
+            .rows
+            .iter()
+            .zip(self.rows.iter().skip(1))
+            .find(|(_, above)| p >= above.pressure.0)?;
+
+        let HectoPascal(p_below) = below.pressure;
+        let HectoPascal(p_above) = above.pressure;
+        let frac = (p_below.ln() - p.ln()) / (p_below.ln() - p_above.ln());
+        let interp = |a: f64, b: f64| a + frac * (b - a);
+
+        Some(GrossLimit {
+            pressure,
+            min_temperature: Celsius(interp(below.min_temperature.0, above.min_temperature.0)),
+            max_temperature: Celsius(interp(below.max_temperature.0, above.max_temperature.0)),
+            min_dew_point: Celsius(interp(below.min_dew_point.0, above.min_dew_point.0)),
+            max_dew_point: Celsius(interp(below.max_dew_point.0, above.max_dew_point.0)),
+            max_wind_speed: Knots(interp(below.max_wind_speed.0, above.max_wind_speed.0)),
+        })
+    }
+}
+
+pub(crate) fn check_gross_limits(snd: &Sounding, limits: &GrossLimits, ve: &mut ValidationErrors) {
+    let temperature = snd.temperature_profile();
+    let dew_point = snd.dew_point_profile();
+    let wind = snd.wind_profile();
+
+    for (i, p) in snd.pressure_profile().iter().enumerate() {
+        let limit = match p.into_option().and_then(|p| limits.limits_at(p)) {
+            Some(limit) => limit,
+            None => continue,
+        };
+
+        let mut push_error = |name, val: f64, bound: f64| {
+            ve.push_error(Err(ValidationError::GrossLimitExceeded(
+                name,
+                Level::from_sounding(snd, i),
+                val,
+                bound,
+            )));
+        };
+
+        if let Some(Celsius(t)) = temperature.get(i).and_then(|t| t.into_option()) {
+            if t < limit.min_temperature.0 {
+                push_error("Temperature", t, limit.min_temperature.0);
+            } else if t > limit.max_temperature.0 {
+                push_error("Temperature", t, limit.max_temperature.0);
+            }
+        }
+
+        if let Some(Celsius(dp)) = dew_point.get(i).and_then(|dp| dp.into_option()) {
+            if dp < limit.min_dew_point.0 {
+                push_error("Dew point", dp, limit.min_dew_point.0);
+            } else if dp > limit.max_dew_point.0 {
+                push_error("Dew point", dp, limit.max_dew_point.0);
+            }
+        }
+
+        if let Some(WindSpdDir {
+            speed: Knots(spd), ..
+        }) = wind.get(i).and_then(|w| w.into_option())
+        {
+            if spd > limit.max_wind_speed.0 {
+                push_error("Wind speed", spd, limit.max_wind_speed.0);
+            }
+        }
+    }
+}
@@ -0,0 +1,216 @@
+//! Statistics and checks for missing data in the profiles.
+use crate::error::*;
+use metfor::HectoPascal;
+use optional::{Noned, Optioned};
+use sounding_base::Sounding;
+
+/// The profiles of a sounding, other than pressure.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Profile {
+    /// The temperature profile.
+    Temperature,
+    /// The wet bulb temperature profile.
+    WetBulb,
+    /// The dew point profile.
+    DewPoint,
+    /// The equivalent potential temperature profile.
+    ThetaE,
+    /// The wind profile.
+    Wind,
+    /// The pressure vertical velocity profile.
+    Pvv,
+    /// The geopotential height profile.
+    Height,
+    /// The cloud fraction profile.
+    CloudFraction,
+}
+
+impl Profile {
+    /// The name of the profile used in error messages.
+    pub fn name(self) -> &'static str {
+        use crate::Profile::*;
+
+        match self {
+            Temperature => "Temperature",
+            WetBulb => "Wet bulb temperature",
+            DewPoint => "Dew point",
+            ThetaE => "Theta-e",
+            Wind => "Wind",
+            Pvv => "Omega (pressure vertical velocity)",
+            Height => "Height",
+            CloudFraction => "Cloud fraction",
+        }
+    }
+
+    /// Whether each level of the profile has a value. An empty profile has no values.
+    fn available(self, snd: &Sounding) -> Vec<bool> {
+        use crate::Profile::*;
+
+        fn available<T: Noned + Copy>(profile: &[Optioned<T>]) -> Vec<bool> {
+            profile.iter().map(|val| val.is_some()).collect()
+        }
+
+        match self {
+            Temperature => available(snd.temperature_profile()),
+            WetBulb => available(snd.wet_bulb_profile()),
+            DewPoint => available(snd.dew_point_profile()),
+            ThetaE => available(snd.theta_e_profile()),
+            Wind => available(snd.wind_profile()),
+            Pvv => available(snd.pvv_profile()),
+            Height => available(snd.height_profile()),
+            CloudFraction => available(snd.cloud_fraction_profile()),
+        }
+    }
+}
+
+/// Statistics about the missing values of a profile in a layer.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MissingDataStats {
+    /// The number of levels in the layer.
+    pub num_levels: usize,
+    /// The fraction of the levels in the layer that are missing a value.
+    pub fraction_missing: f64,
+    /// The depth of the deepest run of missing values, measured between the levels with data on
+    /// either side of it. A run at the bottom or top of the layer is measured to the edge of the
+    /// layer, or to the lowest or highest level of the sounding if that is inside the layer.
+    pub longest_gap: HectoPascal,
+}
+
+/// Compute the missing data statistics for a profile in the layer from `bottom` to `top`.
+///
+/// Only the levels above the surface with a pressure are counted, and `None` is returned if
+/// there are no levels in the layer.
+pub fn missing_data_stats<P>(
+    snd: &Sounding,
+    profile: Profile,
+    bottom: P,
+    top: P,
+) -> Option<MissingDataStats>
+where
+    HectoPascal: From<P>,
+{
+    let HectoPascal(bottom) = HectoPascal::from(bottom);
+    let HectoPascal(top) = HectoPascal::from(top);
+    let available = profile.available(snd);
+
+    // Index 0 is the surface.
+    let pressure: Vec<(usize, f64)> = snd
+        .pressure_profile()
+        .iter()
+        .enumerate()
+        .skip(1)
+        .filter_map(|(i, p)| p.into_option().map(|HectoPascal(p)| (i, p)))
+        .collect();
+
+    let levels: Vec<(f64, bool)> = pressure
+        .iter()
+        .filter(|&&(_, p)| p <= bottom && p >= top)
+        .map(|&(i, p)| (p, available.get(i).cloned().unwrap_or(false)))
+        .collect();
+
+    let num_levels = levels.len();
+    if num_levels == 0 {
+        return None;
+    }
+
+    let num_missing = levels.iter().filter(|&&(_, has_data)| !has_data).count();
+
+    // A gap at the edge of the layer runs to the bottom or top of the layer, but not below or
+    // above the levels of the sounding.
+    let lowest = pressure.iter().map(|&(_, p)| p).fold(f64::MIN, f64::max);
+    let highest = pressure.iter().map(|&(_, p)| p).fold(f64::MAX, f64::min);
+    let bottom = bottom.min(lowest);
+    let top = top.max(highest);
+
+    let mut longest_gap = 0.0f64;
+    let mut in_gap = false;
+    let mut last_with_data = bottom;
+    for &(p, has_data) in &levels {
+        if has_data {
+            if in_gap {
+                longest_gap = longest_gap.max(last_with_data - p);
+            }
+            in_gap = false;
+            last_with_data = p;
+        } else {
+            in_gap = true;
+        }
+    }
+    if in_gap {
+        longest_gap = longest_gap.max(last_with_data - top);
+    }
+
+    Some(MissingDataStats {
+        num_levels,
+        fraction_missing: num_missing as f64 / num_levels as f64,
+        longest_gap: HectoPascal(longest_gap),
+    })
+}
+
+/// Limits on the missing data allowed for a profile in a layer.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MissingDataThreshold {
+    /// The profile to check.
+    pub profile: Profile,
+    /// The bottom of the layer.
+    pub bottom: HectoPascal,
+    /// The top of the layer.
+    pub top: HectoPascal,
+    /// The largest fraction of levels in the layer that may be missing.
+    pub max_fraction_missing: f64,
+    /// The deepest gap in the data allowed in the layer.
+    pub max_gap: HectoPascal,
+}
+
+impl MissingDataThreshold {
+    /// Create a threshold for the layer from `bottom` to `top`.
+    pub fn new<P>(
+        profile: Profile,
+        (bottom, top): (P, P),
+        max_fraction_missing: f64,
+        max_gap: P,
+    ) -> Self
+    where
+        HectoPascal: From<P>,
+    {
+        MissingDataThreshold {
+            profile,
+            bottom: HectoPascal::from(bottom),
+            top: HectoPascal::from(top),
+            max_fraction_missing,
+            max_gap: HectoPascal::from(max_gap),
+        }
+    }
+}
+
+pub(crate) fn check_missing_data(
+    snd: &Sounding,
+    thresholds: &[MissingDataThreshold],
+    ve: &mut ValidationErrors,
+) {
+    for threshold in thresholds {
+        let stats =
+            match missing_data_stats(snd, threshold.profile, threshold.bottom, threshold.top) {
+                Some(stats) => stats,
+                None => continue,
+            };
+
+        if stats.fraction_missing > threshold.max_fraction_missing {
+            ve.push_error(Err(ValidationError::TooMuchMissingData(
+                threshold.profile.name(),
+                stats.fraction_missing,
+                threshold.max_fraction_missing,
+            )));
+        }
+
+        let HectoPascal(gap) = stats.longest_gap;
+        let HectoPascal(max_gap) = threshold.max_gap;
+        if gap > max_gap {
+            ve.push_error(Err(ValidationError::MissingDataGap(
+                threshold.profile.name(),
+                gap,
+                max_gap,
+            )));
+        }
+    }
+}
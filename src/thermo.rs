@@ -0,0 +1,229 @@
+//! Checks for the thermodynamic consistency of the profiles.
+use crate::error::*;
+use metfor::{Celsius, CelsiusDiff, HectoPascal, Kelvin, Meters, Quantity};
+use optional::Optioned;
+use sounding_base::Sounding;
+
+/// Filter out pressures that would make the metfor functions panic in debug builds.
+fn valid_pressure(p: Optioned<HectoPascal>) -> Option<HectoPascal> {
+    p.into_option()
+        .filter(|&HectoPascal(p)| p > 0.0 && p.is_finite())
+}
+
+/// Filter out temperatures that would make the metfor functions panic in debug builds.
+fn valid_temperature(t: Optioned<Celsius>) -> Option<Celsius> {
+    t.into_option()
+        .filter(|&Celsius(t)| t.is_finite())
+        .filter(|t| t.into_option().is_some())
+}
+
+pub(crate) fn check_hydrostatic(snd: &Sounding, tolerance: Meters, ve: &mut ValidationErrors) {
+    let Meters(tol) = tolerance;
+    let pressure = snd.pressure_profile();
+    let temperature = snd.temperature_profile();
+    let dew_point = snd.dew_point_profile();
+    let height = snd.height_profile();
+
+    // Use the virtual temperature if the dew point is available.
+    let layer_levels = pressure
+        .iter()
+        .zip(temperature.iter())
+        .zip(height.iter())
+        .enumerate()
+        .filter_map(|(i, ((&p, &t), &z))| {
+            let p = valid_pressure(p)?;
+            let t = valid_temperature(t)?;
+            let Meters(z) = z.into_option()?;
+
+            let tv = dew_point
+                .get(i)
+                .and_then(|&dp| valid_temperature(dp))
+                .and_then(|dp| metfor::virtual_temperature(t, dp, p))
+                .unwrap_or_else(|| Kelvin::from(t));
+
+            Some((i, p.unpack(), tv.unpack(), z))
+        });
+
+    let mut level_below: Option<(usize, f64, f64, f64)> = None;
+    for (i, p, tv, z) in layer_levels {
+        if let Some((i_below, p_below, tv_below, z_below)) = level_below {
+            let tv_mean = (tv + tv_below) / 2.0;
+
+            // Hypsometric equation
+            let thickness = metfor::Rd.unpack() * tv_mean / -metfor::g * (p_below / p).ln();
+            let reported = z - z_below;
+
+            if (thickness - reported).abs() > tol {
+                ve.push_error(Err(ValidationError::HydrostaticInconsistency(
+                    Level::from_sounding(snd, i_below),
+                    Level::from_sounding(snd, i),
+                    thickness,
+                    reported,
+                )));
+            }
+        }
+
+        level_below = Some((i, p, tv, z));
+    }
+}
+
+/// Get the pressure, temperature, and dew point at a level if they are all valid.
+fn pressure_temperature_dew_point(
+    snd: &Sounding,
+    index: usize,
+) -> Option<(HectoPascal, Celsius, Celsius)> {
+    let p = valid_pressure(*snd.pressure_profile().get(index)?)?;
+    let t = valid_temperature(*snd.temperature_profile().get(index)?)?;
+    let dp = valid_temperature(*snd.dew_point_profile().get(index)?)?;
+
+    Some((p, t, dp))
+}
+
+pub(crate) fn check_theta_e(snd: &Sounding, tolerance: CelsiusDiff, ve: &mut ValidationErrors) {
+    let CelsiusDiff(tol) = tolerance;
+
+    for (i, &theta_e) in snd.theta_e_profile().iter().enumerate() {
+        let stored = match theta_e.into_option() {
+            Some(Kelvin(val)) => val,
+            None => continue,
+        };
+
+        let computed =
+            pressure_temperature_dew_point(snd, i).and_then(|(p, t, dp)| metfor::theta_e(t, dp, p));
+
+        if let Some(Kelvin(computed)) = computed {
+            if (stored - computed).abs() > tol {
+                ve.push_error(Err(ValidationError::ThetaEMismatch(
+                    Level::from_sounding(snd, i),
+                    stored,
+                    computed,
+                )));
+            }
+        }
+    }
+}
+
+pub(crate) fn check_wet_bulb(snd: &Sounding, tolerance: CelsiusDiff, ve: &mut ValidationErrors) {
+    let CelsiusDiff(tol) = tolerance;
+
+    for (i, &wet_bulb) in snd.wet_bulb_profile().iter().enumerate() {
+        let stored = match wet_bulb.into_option() {
+            Some(Celsius(val)) => val,
+            None => continue,
+        };
+
+        let computed = pressure_temperature_dew_point(snd, i)
+            .and_then(|(p, t, dp)| metfor::wet_bulb(t, dp, p));
+
+        if let Some(Celsius(computed)) = computed {
+            if (stored - computed).abs() > tol {
+                ve.push_error(Err(ValidationError::WetBulbMismatch(
+                    Level::from_sounding(snd, i),
+                    stored,
+                    computed,
+                )));
+            }
+        }
+    }
+}
+
+pub(crate) fn check_superadiabatic(
+    snd: &Sounding,
+    tolerance: CelsiusDiff,
+    surface_layer_depth: HectoPascal,
+    surface_tolerance: CelsiusDiff,
+    ve: &mut ValidationErrors,
+) {
+    let CelsiusDiff(tol) = tolerance;
+    let CelsiusDiff(sfc_tol) = surface_tolerance;
+    let HectoPascal(depth) = surface_layer_depth;
+
+    let theta = snd
+        .pressure_profile()
+        .iter()
+        .zip(snd.temperature_profile())
+        .enumerate()
+        .filter_map(|(i, (&p, &t))| {
+            let p = valid_pressure(p)?;
+            let t = valid_temperature(t)?;
+            let Kelvin(theta) = metfor::theta(p, t);
+
+            Some((i, p.unpack(), theta))
+        });
+
+    // The surface layer is measured from the station pressure, or the lowest level if that is
+    // missing.
+    let mut sfc_pressure = valid_pressure(snd.station_pressure()).map(|HectoPascal(p)| p);
+
+    let mut level_below: Option<(usize, f64)> = None;
+    for (i, p, theta) in theta {
+        let sfc_p = *sfc_pressure.get_or_insert(p);
+
+        if let Some((i_below, theta_below)) = level_below {
+            let decrease = theta_below - theta;
+            let in_surface_layer = sfc_p - p <= depth;
+
+            let severity = if decrease > sfc_tol || (decrease > tol && !in_surface_layer) {
+                Some(Severity::Error)
+            } else if decrease > tol {
+                Some(Severity::Warning)
+            } else {
+                None
+            };
+
+            if let Some(severity) = severity {
+                ve.push_with_severity(
+                    Err(ValidationError::SuperadiabaticLapseRate(
+                        Level::from_sounding(snd, i_below),
+                        Level::from_sounding(snd, i),
+                        theta_below,
+                        theta,
+                    )),
+                    severity,
+                );
+            }
+        }
+
+        level_below = Some((i, theta));
+    }
+}
+
+pub(crate) fn check_mslp_reduction(
+    snd: &Sounding,
+    tolerance: HectoPascal,
+    ve: &mut ValidationErrors,
+) {
+    let HectoPascal(tol) = tolerance;
+
+    let (HectoPascal(mslp), stn) = match (
+        snd.mslp().into_option(),
+        valid_pressure(snd.station_pressure()),
+    ) {
+        (Some(mslp), Some(stn)) => (mslp, stn),
+        _ => return,
+    };
+    let Meters(elevation) = match snd.station_info().elevation().into_option() {
+        Some(elevation) => elevation,
+        None => return,
+    };
+
+    if elevation > 0.0 && mslp < stn.unpack() {
+        ve.push_error(Err(ValidationError::MslpLessThanStationPressure(
+            mslp,
+            stn.unpack(),
+        )));
+        return;
+    }
+
+    // Reduce with the hypsometric equation, assuming a standard lapse rate below the station.
+    if let Some(t) = valid_temperature(snd.sfc_temperature()) {
+        let Kelvin(t) = Kelvin::from(t);
+        let t_mean = t + 0.0065 * elevation / 2.0;
+        let computed =
+            stn.unpack() * (-metfor::g * elevation / (metfor::Rd.unpack() * t_mean)).exp();
+
+        if (computed - mslp).abs() > tol {
+            ve.push_error(Err(ValidationError::MslpMismatch(mslp, computed)));
+        }
+    }
+}
@@ -0,0 +1,79 @@
+//! Heuristics for values stored with the wrong units.
+use crate::error::*;
+use crate::validate::detect_cloud_scale;
+use crate::validator::CloudScale;
+use metfor::{Celsius, HectoPascal, Quantity};
+use optional::{Noned, Optioned};
+use sounding_base::Sounding;
+
+/// Whether more than half of the available values in a profile are above `threshold`.
+fn mostly_above<T>(profile: &[Optioned<T>], threshold: f64) -> bool
+where
+    T: Noned + Copy + Quantity,
+{
+    let (num, num_above) = profile
+        .iter()
+        .filter_map(|val| val.into_option())
+        .fold((0, 0), |(num, num_above), val| {
+            (num + 1, num_above + (val.unpack() > threshold) as usize)
+        });
+
+    num_above * 2 > num
+}
+
+pub(crate) fn check_units(snd: &Sounding, cloud_scale: CloudScale, ve: &mut ValidationErrors) {
+    let mut push_error = |name, unit| {
+        ve.push_error(Err(ValidationError::SuspectedUnitError(name, unit)));
+    };
+
+    // The hottest temperatures on record are below 60C, while Kelvin are always above 150.
+    let temperatures: [(&'static str, &[Optioned<Celsius>]); 3] = [
+        ("Temperature", snd.temperature_profile()),
+        ("Wet bulb temperature", snd.wet_bulb_profile()),
+        ("Dew point", snd.dew_point_profile()),
+    ];
+    for &(name, profile) in temperatures.iter() {
+        if mostly_above(profile, 100.0) {
+            push_error(name, "K");
+        }
+    }
+
+    // The highest sea level pressures on record are below 1090 hPa.
+    if mostly_above(snd.pressure_profile(), 1100.0) {
+        push_error("Pressure", "Pa");
+    }
+    if let Some(HectoPascal(mslp)) = snd.mslp().into_option() {
+        if mslp > 1100.0 {
+            push_error("MSLP", "Pa");
+        }
+    }
+
+    // With a configured cloud scale, most of the cloud fraction profile should be in that scale.
+    // A profile of only clear sky looks the same in either scale.
+    let cloud_fraction = snd.cloud_fraction_profile();
+    let has_cloud = cloud_fraction
+        .iter()
+        .any(|cld| cld.into_option().is_some_and(|cld| cld != 0.0));
+    if has_cloud {
+        match (cloud_scale, detect_cloud_scale(cloud_fraction)) {
+            (CloudScale::Fraction, CloudScale::Percent) => push_error("Cloud fraction", "%"),
+            (CloudScale::Percent, CloudScale::Fraction) => push_error("Cloud fraction", "fraction"),
+            _ => {}
+        }
+    }
+
+    // Winds over 250 knots have never been observed. Speeds in km/h (1.85 times knots) and
+    // speeds in knots that were converted from m/s again (1.94 times knots) both look like this,
+    // so the unit is ambiguous. Speeds in m/s stored as knots are too slow rather than too fast,
+    // and are not detected.
+    let max_speed = snd
+        .wind_profile()
+        .iter()
+        .chain(std::iter::once(&snd.sfc_wind()))
+        .filter_map(|wind| wind.into_option())
+        .map(|wind| wind.speed.unpack())
+        .fold(0.0f64, f64::max);
+    if max_speed > 250.0 {
+        push_error("Wind speed", "km/h, or knots converted as if they were m/s");
+    }
+}
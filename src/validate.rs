@@ -1,25 +1,66 @@
 use crate::error::*;
-use metfor::{Celsius, HectoPascal, Meters, WindSpdDir};
+use crate::validator::{CloudScale, SoundingTop, Validator};
+use chrono::NaiveDateTime;
+use metfor::{Celsius, CelsiusDiff, HectoPascal, Knots, Meters, Quantity, WindSpdDir};
 use sounding_base::Sounding;
 
 use optional::{some, Optioned};
 
 macro_rules! validate_f64_positive {
-    ($var:expr, $var_name:expr, $err_list:ident) => {
+    ($var:expr, $var_name:expr, $level:expr, $err_list:ident) => {
         if let Some(val) = $var.into_option() {
             let val: f64 = metfor::Quantity::unpack(val);
             if val < 0.0 {
-                $err_list.push_error(Err(ValidationError::InvalidNegativeValue($var_name, val)));
+                $err_list.push_error(Err(ValidationError::InvalidNegativeValue(
+                    $var_name, $level, val,
+                )));
+            }
+        }
+    };
+}
+
+macro_rules! validate_f64_finite {
+    ($var:expr, $var_name:expr, $level:expr, $err_list:ident) => {
+        if let Some(val) = $var.into_option() {
+            let val: f64 = metfor::Quantity::unpack(val);
+            if !val.is_finite() {
+                $err_list.push_error(Err(ValidationError::NonFiniteValue($var_name, $level, val)));
+            }
+        }
+    };
+}
+
+macro_rules! validate_profile_finite {
+    ($snd:ident, $profile:ident, $var_name:expr, $err_list:ident) => {
+        for (i, val) in $snd.$profile().iter().enumerate() {
+            validate_f64_finite!(
+                *val,
+                $var_name,
+                Some(Level::from_sounding($snd, i)),
+                $err_list
+            );
+        }
+    };
+}
+
+// Only checks the upper bound, validate_f64_positive! reports negative values.
+macro_rules! validate_f64_range {
+    ($var:expr, $var_name:expr, $level:expr, ($min:expr, $max:expr), $err_list:ident) => {
+        if let Some(val) = $var.into_option() {
+            if val > $max {
+                $err_list.push_error(Err(ValidationError::OutOfRange(
+                    $var_name, $level, val, $min, $max,
+                )));
             }
         }
     };
 }
 
 macro_rules! validate_wind_direction {
-    ($var:expr, $err_list:ident) => {
+    ($var:expr, $level:expr, $err_list:ident) => {
         if let Some(val) = $var.into() {
-            if val < 0.0 || val > 360.0 {
-                $err_list.push_error(Err(ValidationError::InvalidWindDirection(val)));
+            if !(0.0..=360.0).contains(&val) {
+                $err_list.push_error(Err(ValidationError::InvalidWindDirection($level, val)));
             }
         }
     };
@@ -27,89 +68,594 @@ macro_rules! validate_wind_direction {
 
 /// Validates the sounding with some simple sanity checks. For instance, checks that pressure
 /// decreases with height.
+///
+/// This runs the default preset of checks, see `Validator` to configure them.
 pub fn validate(snd: &Sounding) -> Result<(), ValidationErrors> {
-    let mut err_return = ValidationErrors::new();
+    Validator::new().validate(snd)
+}
 
-    let pressure = snd.pressure_profile();
+/// Change the direction of north winds from 360 to 0, in the wind profile and the surface wind.
+///
+/// Use this before validating with `Validator::with_wind_north_as_zero` for data that reports
+/// north winds as 360.
+pub fn normalize_north_winds(snd: Sounding) -> Sounding {
+    let normalize = |wind: Optioned<WindSpdDir<Knots>>| {
+        wind.map_t(|wind| {
+            if wind.direction == 360.0 {
+                WindSpdDir {
+                    direction: 0.0,
+                    ..wind
+                }
+            } else {
+                wind
+            }
+        })
+    };
 
-    //
-    // Sounding checks
-    //
+    // Index 0 is the surface wind, which the profile setter adds back.
+    let sfc_wind = normalize(snd.sfc_wind());
+    let wind: Vec<_> = snd
+        .wind_profile()
+        .iter()
+        .skip(1)
+        .cloned()
+        .map(normalize)
+        .collect();
+    snd.with_sfc_wind(sfc_wind).with_wind_profile(wind)
+}
 
-    // Pressure required as vertical coordinate.
-    err_return.push_error(check_pressure_exists(pressure));
+pub(crate) fn check_vector_lengths(snd: &Sounding, ve: &mut ValidationErrors) {
+    let len = snd.pressure_profile().len();
 
-    let len = pressure.len();
-    let temperature = snd.temperature_profile();
-    let wet_bulb = snd.wet_bulb_profile();
-    let dew_point = snd.dew_point_profile();
-    let theta_e = snd.theta_e_profile();
-    let wind = snd.wind_profile();
-    let omega = snd.pvv_profile();
-    let height = snd.height_profile();
-    let cloud_fraction = snd.cloud_fraction_profile();
-
-    err_return.push_error(validate_vector_len(temperature, len, "Temperature"));
-    err_return.push_error(validate_vector_len(wet_bulb, len, "Wet bulb temperature"));
-    err_return.push_error(validate_vector_len(dew_point, len, "Dew point"));
-    err_return.push_error(validate_vector_len(theta_e, len, "Theta-e"));
-    err_return.push_error(validate_vector_len(wind, len, "Wind"));
-    err_return.push_error(validate_vector_len(
-        omega,
+    ve.push_error(validate_vector_len(
+        snd.temperature_profile(),
+        len,
+        "Temperature",
+    ));
+    ve.push_error(validate_vector_len(
+        snd.wet_bulb_profile(),
+        len,
+        "Wet bulb temperature",
+    ));
+    ve.push_error(validate_vector_len(
+        snd.dew_point_profile(),
+        len,
+        "Dew point",
+    ));
+    ve.push_error(validate_vector_len(snd.theta_e_profile(), len, "Theta-e"));
+    ve.push_error(validate_vector_len(snd.wind_profile(), len, "Wind"));
+    ve.push_error(validate_vector_len(
+        snd.pvv_profile(),
         len,
         "Omega (pressure vertical velocity)",
     ));
-    err_return.push_error(validate_vector_len(height, len, "Height"));
-    err_return.push_error(validate_vector_len(cloud_fraction, len, "Cloud fraction"));
+    ve.push_error(validate_vector_len(snd.height_profile(), len, "Height"));
+    ve.push_error(validate_vector_len(
+        snd.cloud_fraction_profile(),
+        len,
+        "Cloud fraction",
+    ));
+}
 
-    // Check that pressure always decreases with height and that the station pressure is more
-    // than the lowest pressure level in sounding. AND..
-    // Check height always increases with height.
-    err_return.push_error(check_vertical_height_pressure(snd));
+pub(crate) fn check_finite(snd: &Sounding, ve: &mut ValidationErrors) {
+    // NaN is the missing value for the optional types used by Sounding, so only infinities can
+    // be found in the profiles and surface values.
+    validate_profile_finite!(snd, pressure_profile, "Pressure", ve);
+    validate_profile_finite!(snd, temperature_profile, "Temperature", ve);
+    validate_profile_finite!(snd, wet_bulb_profile, "Wet bulb temperature", ve);
+    validate_profile_finite!(snd, dew_point_profile, "Dew point", ve);
+    validate_profile_finite!(snd, theta_e_profile, "Theta-e", ve);
+    validate_profile_finite!(snd, pvv_profile, "Omega (pressure vertical velocity)", ve);
+    validate_profile_finite!(snd, height_profile, "Height", ve);
+    validate_profile_finite!(snd, cloud_fraction_profile, "Cloud fraction", ve);
+    for (i, wind) in snd.wind_profile().iter().enumerate() {
+        if let Some(WindSpdDir { speed, direction }) = wind.into_option() {
+            let level = Some(Level::from_sounding(snd, i));
+            validate_f64_finite!(some(speed), "Wind speed", level, ve);
+            validate_f64_finite!(some(direction), "Wind direction", level, ve);
+        }
+    }
 
-    // Check that dew point <= wet bulb <= t
-    check_temp_wet_bulb_dew_point(snd, &mut err_return);
+    validate_f64_finite!(snd.station_pressure(), "Station pressure", None, ve);
+    validate_f64_finite!(snd.mslp(), "MSLP", None, ve);
+    validate_f64_finite!(snd.sfc_temperature(), "Surface temperature", None, ve);
+    validate_f64_finite!(snd.sfc_dew_point(), "Surface dew point", None, ve);
+    validate_f64_finite!(snd.precipitation(), "Precipitation", None, ve);
+    validate_f64_finite!(snd.low_cloud(), "Low cloud", None, ve);
+    validate_f64_finite!(snd.mid_cloud(), "Mid cloud", None, ve);
+    validate_f64_finite!(snd.high_cloud(), "Hi cloud", None, ve);
+    if let Some(WindSpdDir { speed, direction }) = snd.sfc_wind().into_option() {
+        validate_f64_finite!(some(speed), "Surface wind speed", None, ve);
+        validate_f64_finite!(some(direction), "Surface wind direction", None, ve);
+    }
 
+    // The station location is not optional, so NaN is an error there too.
+    let info = snd.station_info();
+    validate_f64_finite!(info.elevation(), "Station elevation", None, ve);
+    if let Some((lat, lon)) = info.location() {
+        for &(name, val) in [("Latitude", lat), ("Longitude", lon)].iter() {
+            if !val.is_finite() {
+                ve.push_error(Err(ValidationError::NonFiniteValue(name, None, val)));
+            }
+        }
+    }
+}
+
+pub(crate) fn check_wind(snd: &Sounding, ve: &mut ValidationErrors) {
     // Check that speed >= 0 and direction 0-360
-    for wind_val in wind {
+    for (i, wind_val) in snd.wind_profile().iter().enumerate() {
         if let Some(WindSpdDir {
             speed: spd,
             direction: dir,
         }) = wind_val.into_option()
         {
-            validate_f64_positive!(some(spd), "Wind speed", err_return);
-            validate_wind_direction!(dir, err_return);
+            validate_f64_positive!(
+                some(spd),
+                "Wind speed",
+                Some(Level::from_sounding(snd, i)),
+                ve
+            );
+            validate_wind_direction!(dir, Some(Level::from_sounding(snd, i)), ve);
+        }
+    }
+
+    if let Some(WindSpdDir {
+        speed: spd,
+        direction: dir,
+    }) = snd.sfc_wind().into_option()
+    {
+        validate_f64_positive!(some(spd), "Wind speed", None, ve);
+        validate_wind_direction!(dir, None, ve);
+    }
+}
+
+pub(crate) fn check_wind_consistency(
+    snd: &Sounding,
+    north_as_zero: bool,
+    spike_tolerance: Knots,
+    max_shear_per_km: Knots,
+    shear_min_depth: Meters,
+    ve: &mut ValidationErrors,
+) {
+    let Knots(spike_tol) = spike_tolerance;
+    let Knots(max_shear_per_km) = max_shear_per_km;
+    let Meters(min_depth) = shear_min_depth;
+
+    // Calm winds have direction 0, and north winds 360 unless they are normalized to 0.
+    let mut check_direction = |wind: WindSpdDir<Knots>, level: Option<Level>| {
+        let WindSpdDir {
+            speed: Knots(spd),
+            direction: dir,
+        } = wind;
+
+        if spd == 0.0 && dir != 0.0 {
+            ve.push_error(Err(ValidationError::CalmWindWithDirection(level, dir)));
+        } else if spd > 0.0 && dir == 0.0 && !north_as_zero {
+            ve.push_error(Err(ValidationError::WindSpeedWithoutDirection(level, spd)));
+        } else if dir == 360.0 && north_as_zero {
+            ve.push_error(Err(ValidationError::UnnormalizedWindDirection(level, dir)));
+        }
+    };
+
+    // Index 0 is the surface wind when there is a wind profile.
+    let wind = snd.wind_profile();
+    if wind.is_empty() {
+        if let Some(sfc_wind) = snd.sfc_wind().into_option() {
+            check_direction(sfc_wind, None);
+        }
+    }
+    for (i, w) in wind.iter().enumerate() {
+        if let Some(w) = w.into_option() {
+            check_direction(w, Some(Level::from_sounding(snd, i)));
+        }
+    }
+
+    // The surface wind is usually much slower than the winds aloft, so leave it out.
+    let levels: Vec<(usize, WindSpdDir<Knots>)> = wind
+        .iter()
+        .enumerate()
+        .skip(1)
+        .filter_map(|(i, w)| w.into_option().map(|w| (i, w)))
+        .collect();
+
+    // Speed spikes, a level much faster than the levels above and below it.
+    for triple in levels.windows(3) {
+        let Knots(below) = triple[0].1.speed;
+        let (i, wind) = triple[1];
+        let Knots(spd) = wind.speed;
+        let Knots(above) = triple[2].1.speed;
+
+        if spd - below.max(above) > spike_tol {
+            ve.push_error(Err(ValidationError::WindSpeedSpike(
+                Level::from_sounding(snd, i),
+                spd,
+                below,
+                above,
+            )));
         }
     }
 
+    // Vector shear per km across layers at least the minimum depth deep, so closely spaced levels
+    // are not compared to each other.
+    let height = snd.height_profile();
+    let levels = levels.iter().filter_map(|&(i, w)| {
+        height
+            .get(i)
+            .and_then(|z| z.into_option())
+            .map(|Meters(z)| (i, w, z))
+    });
+    let mut level_below: Option<(usize, WindSpdDir<Knots>, f64)> = None;
+    for (i, w, z) in levels {
+        if let Some((i_below, w_below, z_below)) = level_below {
+            let depth = z - z_below;
+            if depth > 0.0 && depth < min_depth {
+                continue;
+            }
+
+            let depth_km = depth / 1000.0;
+            if depth_km > 0.0 {
+                let Knots(spd) = w.speed;
+                let Knots(spd_below) = w_below.speed;
+                let delta_dir = (w.direction - w_below.direction).to_radians();
+                let shear = (spd * spd + spd_below * spd_below
+                    - 2.0 * spd * spd_below * delta_dir.cos())
+                .max(0.0)
+                .sqrt();
+                let shear = shear / depth_km;
+
+                if shear > max_shear_per_km {
+                    ve.push_error(Err(ValidationError::ExcessiveWindShear(
+                        Level::from_sounding(snd, i_below),
+                        Level::from_sounding(snd, i),
+                        shear,
+                    )));
+                }
+            }
+        }
+        level_below = Some((i, w, z));
+    }
+}
+
+pub(crate) fn check_cloud(snd: &Sounding, scale: CloudScale, ve: &mut ValidationErrors) {
     // Check that cloud fraction >= 0
-    for cld in cloud_fraction {
-        validate_f64_positive!(*cld, "Cloud fraction", err_return);
+    for (i, cld) in snd.cloud_fraction_profile().iter().enumerate() {
+        validate_f64_positive!(
+            *cld,
+            "Cloud fraction",
+            Some(Level::from_sounding(snd, i)),
+            ve
+        );
     }
 
-    // Surface checks
     // Check that hi, mid, and low cloud are all positive or zero
-    validate_f64_positive!(snd.low_cloud(), "Low cloud", err_return);
-    validate_f64_positive!(snd.mid_cloud(), "Mid cloud", err_return);
-    validate_f64_positive!(snd.high_cloud(), "Hi cloud", err_return);
+    validate_f64_positive!(snd.low_cloud(), "Low cloud", None, ve);
+    validate_f64_positive!(snd.mid_cloud(), "Mid cloud", None, ve);
+    validate_f64_positive!(snd.high_cloud(), "Hi cloud", None, ve);
 
-    if let Some(WindSpdDir {
-        speed: spd,
-        direction: dir,
-    }) = snd.sfc_wind().into_option()
+    // Sources disagree on whether the cloud fraction profile is a fraction or a percentage.
+    let profile = snd.cloud_fraction_profile();
+    let max = match scale {
+        CloudScale::Auto => match detect_cloud_scale(profile) {
+            CloudScale::Percent => 100.0,
+            _ => 1.0,
+        },
+        CloudScale::Fraction => 1.0,
+        CloudScale::Percent => 100.0,
+    };
+    for (i, cld) in profile.iter().enumerate() {
+        validate_f64_range!(
+            *cld,
+            "Cloud fraction",
+            Some(Level::from_sounding(snd, i)),
+            (0.0, max),
+            ve
+        );
+    }
+
+    // Low, mid, and high cloud are always fractions.
+    validate_f64_range!(snd.low_cloud(), "Low cloud", None, (0.0, 1.0), ve);
+    validate_f64_range!(snd.mid_cloud(), "Mid cloud", None, (0.0, 1.0), ve);
+    validate_f64_range!(snd.high_cloud(), "Hi cloud", None, (0.0, 1.0), ve);
+}
+
+/// Guess whether a cloud profile is a fraction or a percentage, it is a percentage if most of the
+/// non-zero values are more than 1. Zeros are left out since clear layers look the same on either
+/// scale, and a single bad value can't change the scale of the rest of the profile.
+pub(crate) fn detect_cloud_scale(profile: &[Optioned<f64>]) -> CloudScale {
+    let (num, num_above) = profile
+        .iter()
+        .filter_map(|cld| cld.into_option())
+        .filter(|&cld| cld != 0.0)
+        .fold((0, 0), |(num, num_above), cld| {
+            (num + 1, num_above + usize::from(cld > 1.0))
+        });
+
+    if num_above * 2 > num {
+        CloudScale::Percent
+    } else {
+        CloudScale::Fraction
+    }
+}
+
+pub(crate) fn check_mslp(snd: &Sounding, ve: &mut ValidationErrors) {
+    validate_f64_positive!(snd.mslp(), "MSLP", None, ve);
+}
+
+pub(crate) fn check_station_pressure(snd: &Sounding, ve: &mut ValidationErrors) {
+    validate_f64_positive!(snd.station_pressure(), "Station pressure", None, ve);
+}
+
+pub(crate) fn check_station_info(snd: &Sounding, ve: &mut ValidationErrors) {
+    let info = snd.station_info();
+
+    if let Some(num) = info.station_num().into_option() {
+        if num < 0 {
+            ve.push_error(Err(ValidationError::InvalidNegativeValue(
+                "Station number",
+                None,
+                f64::from(num),
+            )));
+        }
+    }
+
+    // Swapped coordinates usually show up as a latitude out of range.
+    if let Some((lat, lon)) = info.location() {
+        if !(-90.0..=90.0).contains(&lat) {
+            ve.push_error(Err(ValidationError::OutOfRange(
+                "Latitude", None, lat, -90.0, 90.0,
+            )));
+        }
+        if !(-180.0..360.0).contains(&lon) {
+            ve.push_error(Err(ValidationError::OutOfRange(
+                "Longitude",
+                None,
+                lon,
+                -180.0,
+                360.0,
+            )));
+        }
+    }
+
+    if let Some(Meters(elev)) = info.elevation().into_option() {
+        if !(-500.0..=9000.0).contains(&elev) {
+            ve.push_error(Err(ValidationError::OutOfRange(
+                "Station elevation",
+                None,
+                elev,
+                -500.0,
+                9000.0,
+            )));
+        }
+    }
+}
+
+pub(crate) fn check_station_elevation(
+    snd: &Sounding,
+    tolerance: Meters,
+    ve: &mut ValidationErrors,
+) {
+    let Meters(tol) = tolerance;
+
+    let Meters(elev) = match snd.station_info().elevation().into_option() {
+        Some(elev) => elev,
+        None => return,
+    };
+
+    // Index 0 is the station elevation itself, and an elevation above the lowest level is
+    // reported by the vertical order check.
+    let lowest = snd
+        .height_profile()
+        .iter()
+        .enumerate()
+        .skip(1)
+        .find_map(|(i, z)| z.into_option().map(|Meters(z)| (i, z)));
+    if let Some((i, z)) = lowest {
+        if z - elev > tol {
+            ve.push_error(Err(ValidationError::StationElevationMismatch(
+                Level::from_sounding(snd, i),
+                elev,
+                z,
+            )));
+        }
+    }
+}
+
+pub(crate) fn check_surface(
+    snd: &Sounding,
+    tolerance: CelsiusDiff,
+    profiles_checked: bool,
+    ve: &mut ValidationErrors,
+) {
+    // When the profiles are present the surface values are at index 0, so they are already
+    // reported if the profiles were checked.
+    let in_profiles = !snd.temperature_profile().is_empty() && !snd.dew_point_profile().is_empty();
+    if !(profiles_checked && in_profiles) {
+        let CelsiusDiff(tol) = tolerance;
+        if let (Some(Celsius(t)), Some(Celsius(dp))) = (
+            snd.sfc_temperature().into_option(),
+            snd.sfc_dew_point().into_option(),
+        ) {
+            if dp - t > tol {
+                ve.push_error(Err(ValidationError::SurfaceTemperatureLessThanDewPoint(
+                    t, dp,
+                )));
+            }
+        }
+    }
+
+    validate_f64_positive!(snd.precipitation(), "Precipitation", None, ve);
+}
+
+pub(crate) fn check_surface_profile(
+    snd: &Sounding,
+    pressure_tolerance: HectoPascal,
+    tolerance: CelsiusDiff,
+    ve: &mut ValidationErrors,
+) {
+    let HectoPascal(p_tol) = pressure_tolerance;
+    let CelsiusDiff(tol) = tolerance;
+
+    let HectoPascal(station_pressure) = match snd.station_pressure().into_option() {
+        Some(p) => p,
+        None => return,
+    };
+
+    // Only compare to the lowest level aloft, and only if it is at the station pressure.
+    let i = match snd
+        .pressure_profile()
+        .iter()
+        .enumerate()
+        .skip(1)
+        .find_map(|(i, p)| p.into_option().map(|p| (i, p)))
     {
-        validate_f64_positive!(some(spd), "Wind speed", err_return);
-        validate_wind_direction!(dir, err_return);
+        Some((i, HectoPascal(p))) if (p - station_pressure).abs() <= p_tol => i,
+        _ => return,
+    };
+
+    let values = [
+        (
+            "Temperature",
+            snd.sfc_temperature(),
+            snd.temperature_profile(),
+        ),
+        ("Dew point", snd.sfc_dew_point(), snd.dew_point_profile()),
+    ];
+    for &(name, sfc, profile) in values.iter() {
+        let val = profile.get(i).and_then(|val| val.into_option());
+        if let (Some(Celsius(sfc)), Some(Celsius(val))) = (sfc.into_option(), val) {
+            if (sfc - val).abs() > tol {
+                ve.push_error(Err(ValidationError::SurfaceProfileMismatch(
+                    name,
+                    Level::from_sounding(snd, i),
+                    sfc,
+                    val,
+                )));
+            }
+        }
+    }
+}
+
+pub(crate) fn check_lead_time(snd: &Sounding, max_lead_time: i32, ve: &mut ValidationErrors) {
+    if let Some(lt) = snd.lead_time().into_option() {
+        if lt < 0 || lt > max_lead_time {
+            ve.push_error(Err(ValidationError::OutOfRange(
+                "Lead time",
+                None,
+                f64::from(lt),
+                0.0,
+                f64::from(max_lead_time),
+            )));
+        }
     }
+}
 
-    validate_f64_positive!(snd.mslp(), "MSLP", err_return);
+pub(crate) fn check_valid_time(
+    snd: &Sounding,
+    window: (NaiveDateTime, NaiveDateTime),
+    ve: &mut ValidationErrors,
+) {
+    let (start, end) = window;
+    if let Some(vt) = snd.valid_time() {
+        if vt < start || vt > end {
+            ve.push_error(Err(ValidationError::ValidTimeOutsideWindow(vt, start, end)));
+        }
+    }
+}
 
-    validate_f64_positive!(snd.station_pressure(), "Station pressure", err_return);
+pub(crate) fn check_surface_wind(
+    snd: &Sounding,
+    num_levels: usize,
+    speed_tolerance: Knots,
+    direction_tolerance: f64,
+    direction_min_speed: Knots,
+    ve: &mut ValidationErrors,
+) {
+    let Knots(spd_tol) = speed_tolerance;
+    let Knots(min_spd) = direction_min_speed;
 
-    err_return.check_any()
+    let WindSpdDir {
+        speed: Knots(sfc_spd),
+        direction: sfc_dir,
+    } = match snd.sfc_wind().into_option() {
+        Some(wind) => wind,
+        None => return,
+    };
+
+    // Index 0 is the surface wind itself.
+    let levels: Vec<(usize, f64, f64)> = snd
+        .wind_profile()
+        .iter()
+        .enumerate()
+        .skip(1)
+        .filter_map(|(i, w)| w.into_option().map(|w| (i, w.speed.unpack(), w.direction)))
+        .take(num_levels)
+        .collect();
+    let (lowest, lowest_spd, lowest_dir) = match levels.first() {
+        Some(&level) => level,
+        None => return,
+    };
+
+    // The surface wind only has to agree with one of the levels.
+    if !levels
+        .iter()
+        .any(|&(_, spd, _)| (spd - sfc_spd).abs() <= spd_tol)
+    {
+        ve.push_error(Err(ValidationError::SurfaceProfileMismatch(
+            "Wind speed",
+            Level::from_sounding(snd, lowest),
+            sfc_spd,
+            lowest_spd,
+        )));
+    }
+
+    // A surface wind in m/s stored as knots is about half the speed of the profile, and one in
+    // knots converted as if it were m/s about twice the speed. The surface wind is often slower
+    // than the winds aloft, so every level must be close to the ratio.
+    const MPS_TO_KNOTS: f64 = 1.943_844;
+    let near_ratio = |ratio: f64| {
+        levels
+            .iter()
+            .all(|&(_, spd, _)| ((sfc_spd / spd) / ratio - 1.0).abs() <= 0.1)
+    };
+    if near_ratio(1.0 / MPS_TO_KNOTS) {
+        ve.push_error(Err(ValidationError::SuspectedUnitError(
+            "Surface wind speed",
+            "m/s",
+        )));
+    } else if near_ratio(MPS_TO_KNOTS) {
+        ve.push_error(Err(ValidationError::SuspectedUnitError(
+            "Surface wind speed",
+            "knots converted as if they were m/s",
+        )));
+    }
+
+    // The direction of a light wind is not reliable.
+    let direction_difference = |dir: f64| {
+        let diff = (dir - sfc_dir).abs() % 360.0;
+        diff.min(360.0 - diff)
+    };
+    let directions: Vec<f64> = levels
+        .iter()
+        .filter(|&&(_, spd, _)| sfc_spd >= min_spd && spd >= min_spd)
+        .map(|&(_, _, dir)| dir)
+        .collect();
+    if !directions.is_empty()
+        && !directions
+            .iter()
+            .any(|&dir| direction_difference(dir) <= direction_tolerance)
+    {
+        ve.push_error(Err(ValidationError::SurfaceProfileMismatch(
+            "Wind direction",
+            Level::from_sounding(snd, lowest),
+            sfc_dir,
+            lowest_dir,
+        )));
+    }
 }
 
-fn check_pressure_exists(pressure: &[Optioned<HectoPascal>]) -> Result<(), ValidationError> {
+pub(crate) fn check_pressure_exists(
+    pressure: &[Optioned<HectoPascal>],
+) -> Result<(), ValidationError> {
     if pressure.is_empty() {
         Err(ValidationError::NoPressureProfile)
     } else {
@@ -133,70 +679,344 @@ fn validate_vector_len<T>(
     }
 }
 
-fn check_vertical_height_pressure(snd: &Sounding) -> Result<(), ValidationError> {
+pub(crate) fn check_vertical_height_pressure(snd: &Sounding, ve: &mut ValidationErrors) {
+    // Index 0 of the profiles holds the surface values, so the levels aloft start at index 1 and
+    // the station values are compared to the first valid level aloft.
+
     // Check that pressure always decreases with height and that the station pressure is more
     // than the lowest pressure level in sounding.
+    let station_pressure = snd
+        .station_pressure()
+        .into_option()
+        .map(|HectoPascal(val)| val);
     let pressure = snd
         .pressure_profile()
-        .into_iter()
-        .filter_map(|val| val.into_option())
-        .map(|HectoPascal(val)| val);
-    let mut pressure_one_level_down = snd
-        .station_pressure()
-        .map_t(|HectoPascal(val)| val)
-        .unwrap_or(::std::f64::MAX);
-    for pres in pressure {
-        if pressure_one_level_down < pres {
-            return Err(ValidationError::PressureNotDecreasingWithHeight);
+        .iter()
+        .enumerate()
+        .skip(1)
+        .filter_map(|(i, val)| val.into_option().map(|HectoPascal(val)| (i, val)));
+    let mut pressure_one_level_down: Option<f64> = None;
+    for (i, pres) in pressure {
+        match (pressure_one_level_down, station_pressure) {
+            (Some(below), _) if below < pres => {
+                ve.push_error(Err(ValidationError::PressureNotDecreasingWithHeight(
+                    Level::from_sounding(snd, i),
+                    below,
+                    pres,
+                )));
+            }
+            (None, Some(stn)) if stn < pres => {
+                ve.push_error(Err(ValidationError::StationPressureLessThanFirstLevel(
+                    Level::from_sounding(snd, i),
+                    stn,
+                    pres,
+                )));
+            }
+            _ => {}
         }
-        pressure_one_level_down = pres;
+        pressure_one_level_down = Some(pres);
     }
 
-    // Check height always increases with height.
-    let height = snd
-        .height_profile()
-        .into_iter()
-        .filter_map(|val| val.into_option())
-        .map(|Meters(val)| val);
-    let mut height_one_level_down = snd
+    // Check height always increases with height and that the station elevation is below the
+    // lowest height in the sounding.
+    let elevation = snd
         .station_info()
         .elevation()
-        .map(|Meters(val)| val)
-        .unwrap_or(::std::f64::MIN);
-    for hght in height {
-        if height_one_level_down > hght {
-            return Err(ValidationError::PressureNotDecreasingWithHeight);
+        .into_option()
+        .map(|Meters(val)| val);
+    let height = snd
+        .height_profile()
+        .iter()
+        .enumerate()
+        .skip(1)
+        .filter_map(|(i, val)| val.into_option().map(|Meters(val)| (i, val)));
+    let mut height_one_level_down: Option<f64> = None;
+    for (i, hght) in height {
+        match (height_one_level_down, elevation) {
+            (Some(below), _) if below > hght => {
+                ve.push_error(Err(ValidationError::HeightNotIncreasingWithHeight(
+                    Level::from_sounding(snd, i),
+                    below,
+                    hght,
+                )));
+            }
+            (None, Some(elev)) if elev > hght => {
+                ve.push_error(Err(ValidationError::StationElevationAboveFirstHeight(
+                    Level::from_sounding(snd, i),
+                    elev,
+                    hght,
+                )));
+            }
+            _ => {}
+        }
+        height_one_level_down = Some(hght);
+    }
+}
+
+pub(crate) fn check_vertical_resolution(
+    snd: &Sounding,
+    max_pressure_gap: HectoPascal,
+    max_height_gap: Meters,
+    min_levels: usize,
+    ve: &mut ValidationErrors,
+) {
+    let HectoPascal(max_p_gap) = max_pressure_gap;
+    let Meters(max_z_gap) = max_height_gap;
+
+    // Count the levels aloft, index 0 is the surface.
+    let num_levels = snd
+        .pressure_profile()
+        .iter()
+        .skip(1)
+        .filter(|p| p.is_some())
+        .count();
+    if num_levels < min_levels {
+        ve.push_error(Err(ValidationError::TooFewLevels(num_levels, min_levels)));
+    }
+
+    // The gap between the surface and the first level aloft counts too.
+    let mut level_below: Option<(usize, f64)> = None;
+    for (i, p) in snd.pressure_profile().iter().enumerate() {
+        if let Some(HectoPascal(p)) = p.into_option() {
+            if let Some((i_below, below)) = level_below {
+                if below - p > max_p_gap {
+                    ve.push_error(Err(ValidationError::PressureGap(
+                        Level::from_sounding(snd, i_below),
+                        Level::from_sounding(snd, i),
+                        below - p,
+                    )));
+                }
+            }
+            level_below = Some((i, p));
+        }
+    }
+
+    let mut level_below: Option<(usize, f64)> = None;
+    for (i, z) in snd.height_profile().iter().enumerate() {
+        if let Some(Meters(z)) = z.into_option() {
+            if let Some((i_below, below)) = level_below {
+                if z - below > max_z_gap {
+                    ve.push_error(Err(ValidationError::HeightGap(
+                        Level::from_sounding(snd, i_below),
+                        Level::from_sounding(snd, i),
+                        z - below,
+                    )));
+                }
+            }
+            level_below = Some((i, z));
+        }
+    }
+}
+
+pub(crate) fn check_sounding_top(snd: &Sounding, top: SoundingTop, ve: &mut ValidationErrors) {
+    let temperature = snd.temperature_profile();
+    let has_temperature = |i: usize| temperature.get(i).is_some_and(|t| t.is_some());
+
+    // Find the highest level with a temperature, and whether it is high enough.
+    let (highest, reached) = match top {
+        SoundingTop::Pressure(HectoPascal(top)) => {
+            let highest = snd
+                .pressure_profile()
+                .iter()
+                .enumerate()
+                .filter(|&(i, _)| has_temperature(i))
+                .filter_map(|(i, p)| p.into_option().map(|HectoPascal(p)| (i, p)))
+                .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+            (
+                highest.map(|(i, _)| i),
+                highest.is_some_and(|(_, p)| p <= top),
+            )
+        }
+        SoundingTop::Height(Meters(top)) => {
+            let highest = snd
+                .height_profile()
+                .iter()
+                .enumerate()
+                .filter(|&(i, _)| has_temperature(i))
+                .filter_map(|(i, z)| z.into_option().map(|Meters(z)| (i, z)))
+                .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+            (
+                highest.map(|(i, _)| i),
+                highest.is_some_and(|(_, z)| z >= top),
+            )
+        }
+    };
+
+    if !reached {
+        ve.push_error(Err(ValidationError::EarlyTermination(
+            highest.map(|i| Level::from_sounding(snd, i)),
+        )));
+    }
+}
+
+pub(crate) fn check_mandatory_levels(
+    snd: &Sounding,
+    levels: &[HectoPascal],
+    allow_spanned: bool,
+    ve: &mut ValidationErrors,
+) {
+    // Pressures of the levels with a temperature and height.
+    let temperature = snd.temperature_profile();
+    let height = snd.height_profile();
+    let usable: Vec<f64> = snd
+        .pressure_profile()
+        .iter()
+        .enumerate()
+        .filter(|&(i, _)| {
+            temperature.get(i).is_some_and(|t| t.is_some())
+                && height.get(i).is_some_and(|z| z.is_some())
+        })
+        .filter_map(|(_, p)| p.into_option().map(|HectoPascal(p)| p))
+        .collect();
+
+    for &HectoPascal(level) in levels {
+        // Allow for mandatory levels stored with some rounding error.
+        let contains = usable.iter().any(|&p| (p - level).abs() < 0.1);
+        let spans = usable.iter().any(|&p| p > level) && usable.iter().any(|&p| p < level);
+
+        if !(contains || allow_spanned && spans) {
+            ve.push_error(Err(ValidationError::MissingMandatoryLevel(level)));
         }
-        height_one_level_down = hght;
     }
+}
+
+pub(crate) fn check_duplicate_levels(
+    snd: &Sounding,
+    epsilon: HectoPascal,
+    tolerance: CelsiusDiff,
+    wind_tolerance: Knots,
+    ve: &mut ValidationErrors,
+) {
+    let HectoPascal(eps) = epsilon;
+
+    // Index 0 is the surface, which is often repeated as the first level aloft.
+    let pressure = snd
+        .pressure_profile()
+        .iter()
+        .enumerate()
+        .skip(1)
+        .filter_map(|(i, val)| val.into_option().map(|HectoPascal(val)| (i, val)));
+
+    let mut level_below: Option<(usize, f64)> = None;
+    for (i, pres) in pressure {
+        if let Some((i_below, below)) = level_below {
+            if (below - pres).abs() <= eps {
+                let lvl_below = Level::from_sounding(snd, i_below);
+                let lvl = Level::from_sounding(snd, i);
+
+                if rows_conflict(snd, (i_below, i), below == pres, tolerance, wind_tolerance) {
+                    ve.push_error(Err(ValidationError::ConflictingDuplicateLevel(
+                        lvl_below, lvl,
+                    )));
+                } else {
+                    ve.push_warning(Err(ValidationError::DuplicateLevel(lvl_below, lvl)));
+                }
+            }
+        }
+        level_below = Some((i, pres));
+    }
+}
+
+/// Two rows conflict if any variable is available in both and the values differ by more than
+/// the tolerance. Variables that change quickly with pressure, such as height, are only compared
+/// when the pressures are the same.
+fn rows_conflict(
+    snd: &Sounding,
+    (i, j): (usize, usize),
+    same_pressure: bool,
+    tolerance: CelsiusDiff,
+    wind_tolerance: Knots,
+) -> bool {
+    fn differ<T, F>(profile: &[Optioned<T>], i: usize, j: usize, distance: F, tol: f64) -> bool
+    where
+        T: optional::Noned + Copy,
+        F: Fn(T, T) -> f64,
+    {
+        match (profile.get(i), profile.get(j)) {
+            (Some(a), Some(b)) => match (a.into_option(), b.into_option()) {
+                (Some(a), Some(b)) => distance(a, b) > tol,
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    fn difference<T: Quantity>(a: T, b: T) -> f64 {
+        (a.unpack() - b.unpack()).abs()
+    }
+
+    let CelsiusDiff(tol) = tolerance;
+    let Knots(wind_tol) = wind_tolerance;
+    let wind = |a: WindSpdDir<Knots>, b: WindSpdDir<Knots>| {
+        // The magnitude of the vector difference, by the law of cosines.
+        let (Knots(a_spd), Knots(b_spd)) = (a.speed, b.speed);
+        let angle = (a.direction - b.direction).to_radians();
+        (a_spd * a_spd + b_spd * b_spd - 2.0 * a_spd * b_spd * angle.cos())
+            .max(0.0)
+            .sqrt()
+    };
+
+    let conflict = differ(snd.temperature_profile(), i, j, difference, tol)
+        || differ(snd.wet_bulb_profile(), i, j, difference, tol)
+        || differ(snd.dew_point_profile(), i, j, difference, tol)
+        || differ(snd.theta_e_profile(), i, j, difference, tol)
+        || differ(snd.wind_profile(), i, j, wind, wind_tol);
+
+    let exact_conflict = same_pressure
+        && (differ(snd.pvv_profile(), i, j, difference, 0.0)
+            || differ(snd.height_profile(), i, j, difference, 0.0)
+            || differ(
+                snd.cloud_fraction_profile(),
+                i,
+                j,
+                |a: f64, b| (a - b).abs(),
+                0.0,
+            ));
 
-    Ok(())
+    conflict || exact_conflict
 }
 
-fn check_temp_wet_bulb_dew_point(snd: &Sounding, ve: &mut ValidationErrors) {
+pub(crate) fn check_temp_wet_bulb_dew_point(
+    snd: &Sounding,
+    tolerance: CelsiusDiff,
+    ve: &mut ValidationErrors,
+) {
+    let CelsiusDiff(tol) = tolerance;
     let temperature = snd.temperature_profile();
     let wet_bulb = snd.wet_bulb_profile();
     let dew_point = snd.dew_point_profile();
 
-    // Check that dew point <= wet bulb <= t
-    for (t, wb) in temperature.iter().zip(wet_bulb.iter()) {
+    // Check that dew point <= wet bulb <= t, allowing for the tolerance.
+    for (i, (t, wb)) in temperature.iter().zip(wet_bulb.iter()).enumerate() {
         if let (Some(Celsius(t)), Some(Celsius(wb))) = (t.into_option(), wb.into_option()) {
-            if t < wb {
-                ve.push_error(Err(ValidationError::TemperatureLessThanWetBulb(t, wb)));
+            if wb - t > tol {
+                ve.push_error(Err(ValidationError::TemperatureLessThanWetBulb(
+                    Level::from_sounding(snd, i),
+                    t,
+                    wb,
+                )));
             }
         }
     }
-    for (t, dp) in temperature.iter().zip(dew_point.iter()) {
+    for (i, (t, dp)) in temperature.iter().zip(dew_point.iter()).enumerate() {
         if let (Some(Celsius(t)), Some(Celsius(dp))) = (t.into_option(), dp.into_option()) {
-            if t < dp {
-                ve.push_error(Err(ValidationError::TemperatureLessThanDewPoint(t, dp)));
+            if dp - t > tol {
+                ve.push_error(Err(ValidationError::TemperatureLessThanDewPoint(
+                    Level::from_sounding(snd, i),
+                    t,
+                    dp,
+                )));
             }
         }
     }
-    for (wb, dp) in wet_bulb.iter().zip(dew_point.iter()) {
+    for (i, (wb, dp)) in wet_bulb.iter().zip(dew_point.iter()).enumerate() {
         if let (Some(Celsius(wb)), Some(Celsius(dp))) = (wb.into_option(), dp.into_option()) {
-            if wb < dp {
-                ve.push_error(Err(ValidationError::WetBulbLessThanDewPoint(wb, dp)));
+            if dp - wb > tol {
+                ve.push_error(Err(ValidationError::WetBulbLessThanDewPoint(
+                    Level::from_sounding(snd, i),
+                    wb,
+                    dp,
+                )));
             }
         }
     }
@@ -0,0 +1,984 @@
+//! A configurable set of checks to run on a sounding.
+use crate::check::Check;
+use crate::error::*;
+use crate::limits::*;
+use crate::missing::*;
+use crate::thermo::*;
+use crate::units::*;
+use crate::validate::*;
+use chrono::NaiveDateTime;
+use metfor::{CelsiusDiff, HectoPascal, Knots, Meters};
+use sounding_base::Sounding;
+use std::fmt;
+use std::sync::Arc;
+
+/// A configurable validator.
+///
+/// `Validator::new()` creates the default preset used by `validate`, and the builder methods can
+/// enable or disable individual checks and set their tolerances.
+///
+/// # Examples
+///
+/// ```rust
+/// use metfor::CelsiusDiff;
+/// use sounding_base::Sounding;
+/// use sounding_validate::Validator;
+///
+/// let validator = Validator::new()
+///     .with_cloud_check(false)
+///     .with_temperature_order_tolerance(CelsiusDiff(0.1));
+///
+/// let result = validator.validate(&Sounding::new());
+/// assert!(result.is_err()); // No pressure profile!
+/// ```
+#[derive(Clone, Debug)]
+pub struct Validator {
+    // Profiles
+    pressure_profile: bool,
+    finite: bool,
+    units: bool,
+    vector_lengths: bool,
+    vertical_order: bool,
+    vertical_resolution: bool,
+    max_pressure_gap: HectoPascal,
+    max_height_gap: Meters,
+    min_levels: usize,
+    missing_data_thresholds: Vec<MissingDataThreshold>,
+    sounding_top: bool,
+    sounding_top_requirement: SoundingTop,
+    mandatory_levels: bool,
+    mandatory_levels_list: Vec<HectoPascal>,
+    mandatory_levels_spanned: bool,
+    duplicate_levels: bool,
+    duplicate_levels_epsilon: HectoPascal,
+    duplicate_levels_tolerance: CelsiusDiff,
+    duplicate_levels_wind_tolerance: Knots,
+    temperature_order: bool,
+    temperature_order_tolerance: CelsiusDiff,
+    wind: bool,
+    wind_consistency: bool,
+    wind_north_as_zero: bool,
+    wind_spike_tolerance: Knots,
+    max_wind_shear_per_km: Knots,
+    wind_shear_min_depth: Meters,
+    cloud: bool,
+    cloud_scale: CloudScale,
+    hydrostatic: bool,
+    hydrostatic_tolerance: Meters,
+    theta_e: bool,
+    theta_e_tolerance: CelsiusDiff,
+    wet_bulb: bool,
+    wet_bulb_tolerance: CelsiusDiff,
+    superadiabatic: bool,
+    superadiabatic_tolerance: CelsiusDiff,
+    superadiabatic_surface_layer_depth: HectoPascal,
+    superadiabatic_surface_tolerance: CelsiusDiff,
+    gross_limits: bool,
+    gross_limits_table: GrossLimits,
+
+    // Surface variables
+    mslp: bool,
+    mslp_reduction: bool,
+    mslp_reduction_tolerance: HectoPascal,
+    station_pressure: bool,
+    surface: bool,
+    surface_profile: bool,
+    surface_profile_pressure_tolerance: HectoPascal,
+    surface_profile_tolerance: CelsiusDiff,
+    surface_wind: bool,
+    surface_wind_levels: usize,
+    surface_wind_speed_tolerance: Knots,
+    surface_wind_direction_tolerance: f64,
+    surface_wind_direction_min_speed: Knots,
+    station_info: bool,
+    station_elevation: bool,
+    station_elevation_tolerance: Meters,
+
+    // Time
+    lead_time: bool,
+    max_lead_time: i32,
+    valid_time_window: Option<(NaiveDateTime, NaiveDateTime)>,
+
+    // User defined checks
+    custom: CustomChecks,
+
+    // What makes validation fail
+    failure_threshold: Severity,
+}
+
+/// The scale of the cloud fraction profile.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CloudScale {
+    /// Guess the scale from the values in the profile.
+    Auto,
+    /// Values from 0 to 1.
+    Fraction,
+    /// Values from 0 to 100.
+    Percent,
+}
+
+/// How high the sounding must reach for the sounding top check.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum SoundingTop {
+    Pressure(HectoPascal),
+    Height(Meters),
+}
+
+/// User defined checks registered with a `Validator`, run after the built in checks.
+#[derive(Clone, Default)]
+struct CustomChecks(Vec<Arc<dyn Check + Send + Sync>>);
+
+impl fmt::Debug for CustomChecks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "CustomChecks({} checks)", self.0.len())
+    }
+}
+
+impl Default for Validator {
+    fn default() -> Self {
+        Validator {
+            pressure_profile: true,
+            finite: true,
+            units: false,
+            vector_lengths: true,
+            vertical_order: true,
+            vertical_resolution: false,
+            max_pressure_gap: HectoPascal(100.0),
+            max_height_gap: Meters(3000.0),
+            min_levels: 8,
+            missing_data_thresholds: vec![],
+            sounding_top: false,
+            sounding_top_requirement: SoundingTop::Pressure(HectoPascal(400.0)),
+            mandatory_levels: false,
+            mandatory_levels_list: [850.0, 700.0, 500.0, 300.0, 250.0]
+                .iter()
+                .cloned()
+                .map(HectoPascal)
+                .collect(),
+            mandatory_levels_spanned: false,
+            duplicate_levels: false,
+            duplicate_levels_epsilon: HectoPascal(0.0),
+            duplicate_levels_tolerance: CelsiusDiff(0.5),
+            duplicate_levels_wind_tolerance: Knots(5.0),
+            temperature_order: true,
+            temperature_order_tolerance: CelsiusDiff(0.0),
+            wind: true,
+            wind_consistency: false,
+            wind_north_as_zero: false,
+            wind_spike_tolerance: Knots(30.0),
+            max_wind_shear_per_km: Knots(50.0),
+            wind_shear_min_depth: Meters(300.0),
+            cloud: true,
+            cloud_scale: CloudScale::Auto,
+            hydrostatic: false,
+            hydrostatic_tolerance: Meters(50.0),
+            theta_e: false,
+            theta_e_tolerance: CelsiusDiff(2.0),
+            wet_bulb: false,
+            wet_bulb_tolerance: CelsiusDiff(1.0),
+            superadiabatic: false,
+            superadiabatic_tolerance: CelsiusDiff(1.0),
+            superadiabatic_surface_layer_depth: HectoPascal(50.0),
+            superadiabatic_surface_tolerance: CelsiusDiff(3.0),
+            gross_limits: false,
+            gross_limits_table: GrossLimits::default(),
+
+            mslp: true,
+            mslp_reduction: false,
+            mslp_reduction_tolerance: HectoPascal(5.0),
+            station_pressure: true,
+            surface: true,
+            surface_profile: false,
+            surface_profile_pressure_tolerance: HectoPascal(1.0),
+            surface_profile_tolerance: CelsiusDiff(1.0),
+            surface_wind: false,
+            surface_wind_levels: 3,
+            surface_wind_speed_tolerance: Knots(15.0),
+            surface_wind_direction_tolerance: 60.0,
+            surface_wind_direction_min_speed: Knots(15.0),
+            station_info: true,
+            station_elevation: false,
+            station_elevation_tolerance: Meters(100.0),
+
+            lead_time: false,
+            max_lead_time: 384,
+            valid_time_window: None,
+
+            custom: CustomChecks::default(),
+
+            failure_threshold: Severity::Info,
+        }
+    }
+}
+
+impl Validator {
+    /// Create a validator with the default preset of checks. This is a proxy for default with a
+    /// clearer name.
+    pub fn new() -> Self {
+        Validator::default()
+    }
+
+    /// Enable or disable the check that the sounding has a pressure profile. Enabled by default.
+    pub fn with_pressure_profile_check(mut self, enabled: bool) -> Self {
+        self.pressure_profile = enabled;
+        self
+    }
+
+    /// Enable or disable the check that no profile, surface, or station value is infinite. NaN
+    /// marks a missing value in the profiles and surface values, so it is only an error in the
+    /// station location. Enabled by default.
+    pub fn with_finite_check(mut self, enabled: bool) -> Self {
+        self.finite = enabled;
+        self
+    }
+
+    /// Enable or disable the heuristic check for values stored with the wrong units, such as
+    /// temperatures in Kelvin, pressures in Pa, a cloud fraction profile in a different scale than
+    /// the one set with `with_cloud_scale`, or wind speeds too fast for knots, which may be in
+    /// km/h or be knots converted as if they were m/s. Wind speeds in m/s stored as knots are not
+    /// detected by this check, but see `with_surface_wind_check`. Disabled by default.
+    pub fn with_unit_check(mut self, enabled: bool) -> Self {
+        self.units = enabled;
+        self
+    }
+
+    /// Enable or disable the check that all the profiles have the same length as the pressure
+    /// profile. Enabled by default.
+    pub fn with_vector_length_check(mut self, enabled: bool) -> Self {
+        self.vector_lengths = enabled;
+        self
+    }
+
+    /// Enable or disable the check that pressure decreases and height increases going up the
+    /// sounding, starting from the station pressure and elevation. Enabled by default.
+    pub fn with_vertical_order_check(mut self, enabled: bool) -> Self {
+        self.vertical_order = enabled;
+        self
+    }
+
+    /// Enable or disable the check for large pressure or height gaps between consecutive valid
+    /// levels, and for soundings with too few levels above the surface. Disabled by default.
+    pub fn with_vertical_resolution_check(mut self, enabled: bool) -> Self {
+        self.vertical_resolution = enabled;
+        self
+    }
+
+    /// Set the largest allowed pressure difference between consecutive levels. Defaults to
+    /// 100 hPa.
+    pub fn with_max_pressure_gap<T>(mut self, gap: T) -> Self
+    where
+        HectoPascal: From<T>,
+    {
+        self.max_pressure_gap = HectoPascal::from(gap);
+        self
+    }
+
+    /// Set the largest allowed height difference between consecutive levels. Defaults to 3000 m.
+    pub fn with_max_height_gap<T>(mut self, gap: T) -> Self
+    where
+        Meters: From<T>,
+    {
+        self.max_height_gap = Meters::from(gap);
+        self
+    }
+
+    /// Set the minimum number of levels with a pressure above the surface. Defaults to 8.
+    pub fn with_min_levels(mut self, min_levels: usize) -> Self {
+        self.min_levels = min_levels;
+        self
+    }
+
+    /// Add a limit on the missing data in a profile. Limits are checked in the order they were
+    /// added, and there are none by default.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use metfor::HectoPascal;
+    /// use sounding_validate::{MissingDataThreshold, Profile, Validator};
+    ///
+    /// // No more than 30% of the dew points missing below 500 hPa, and no gaps over 100 hPa.
+    /// let _validator = Validator::new().with_missing_data_threshold(MissingDataThreshold::new(
+    ///     Profile::DewPoint,
+    ///     (HectoPascal(1100.0), HectoPascal(500.0)),
+    ///     0.3,
+    ///     HectoPascal(100.0),
+    /// ));
+    /// ```
+    pub fn with_missing_data_threshold(mut self, threshold: MissingDataThreshold) -> Self {
+        self.missing_data_thresholds.push(threshold);
+        self
+    }
+
+    /// Enable or disable the check that the sounding reaches the top pressure or height with
+    /// temperature data. Disabled by default.
+    pub fn with_sounding_top_check(mut self, enabled: bool) -> Self {
+        self.sounding_top = enabled;
+        self
+    }
+
+    /// Require the sounding to reach this pressure, replacing any top height. Defaults to
+    /// 400 hPa.
+    pub fn with_sounding_top_pressure<T>(mut self, pressure: T) -> Self
+    where
+        HectoPascal: From<T>,
+    {
+        self.sounding_top_requirement = SoundingTop::Pressure(HectoPascal::from(pressure));
+        self
+    }
+
+    /// Require the sounding to reach this height, replacing the top pressure.
+    pub fn with_sounding_top_height<T>(mut self, height: T) -> Self
+    where
+        Meters: From<T>,
+    {
+        self.sounding_top_requirement = SoundingTop::Height(Meters::from(height));
+        self
+    }
+
+    /// Enable or disable the check that the sounding has a temperature and height at each of the
+    /// mandatory levels. Disabled by default.
+    pub fn with_mandatory_level_check(mut self, enabled: bool) -> Self {
+        self.mandatory_levels = enabled;
+        self
+    }
+
+    /// Set the pressure levels required by the mandatory level check. Defaults to 850, 700, 500,
+    /// 300, and 250 hPa.
+    pub fn with_mandatory_levels<T>(mut self, levels: &[T]) -> Self
+    where
+        T: Copy,
+        HectoPascal: From<T>,
+    {
+        self.mandatory_levels_list = levels.iter().map(|&p| HectoPascal::from(p)).collect();
+        self
+    }
+
+    /// Accept mandatory levels that are not in the sounding, as long as the sounding has levels
+    /// with a temperature and height above and below them to interpolate from. Defaults to
+    /// false.
+    pub fn with_mandatory_levels_spanned(mut self, allow_spanned: bool) -> Self {
+        self.mandatory_levels_spanned = allow_spanned;
+        self
+    }
+
+    /// Enable or disable the check for consecutive levels with the same pressure. Duplicates
+    /// where the data agrees are reported as warnings, and those where it conflicts as errors.
+    /// Disabled by default.
+    pub fn with_duplicate_level_check(mut self, enabled: bool) -> Self {
+        self.duplicate_levels = enabled;
+        self
+    }
+
+    /// Set how close the pressures of two levels must be for them to be duplicates. Defaults to
+    /// 0, so only identical pressures are duplicates.
+    pub fn with_duplicate_level_epsilon<T>(mut self, epsilon: T) -> Self
+    where
+        HectoPascal: From<T>,
+    {
+        self.duplicate_levels_epsilon = HectoPascal::from(epsilon);
+        self
+    }
+
+    /// Set how far the temperature, wet bulb, dew point, and theta-e of duplicate levels may
+    /// differ before they conflict. The height, omega, and cloud fraction are only compared when
+    /// the pressures are identical, and then they must be equal. Defaults to 0.5C.
+    pub fn with_duplicate_level_tolerance<T>(mut self, tolerance: T) -> Self
+    where
+        CelsiusDiff: From<T>,
+    {
+        self.duplicate_levels_tolerance = CelsiusDiff::from(tolerance);
+        self
+    }
+
+    /// Set how far apart the winds of duplicate levels may be, as the magnitude of their vector
+    /// difference, before they conflict. Defaults to 5 knots.
+    pub fn with_duplicate_level_wind_tolerance<T>(mut self, tolerance: T) -> Self
+    where
+        Knots: From<T>,
+    {
+        self.duplicate_levels_wind_tolerance = Knots::from(tolerance);
+        self
+    }
+
+    /// Enable or disable the check that dew point <= wet bulb <= temperature. Enabled by default.
+    pub fn with_temperature_order_check(mut self, enabled: bool) -> Self {
+        self.temperature_order = enabled;
+        self
+    }
+
+    /// Set how far the dew point or wet bulb may exceed the temperature (or the dew point the wet
+    /// bulb) before it is an error, useful for data that has been rounded. Defaults to 0.
+    pub fn with_temperature_order_tolerance<T>(mut self, tolerance: T) -> Self
+    where
+        CelsiusDiff: From<T>,
+    {
+        self.temperature_order_tolerance = CelsiusDiff::from(tolerance);
+        self
+    }
+
+    /// Enable or disable the check that wind speeds are not negative and that wind directions are
+    /// in the range 0-360. Enabled by default.
+    pub fn with_wind_check(mut self, enabled: bool) -> Self {
+        self.wind = enabled;
+        self
+    }
+
+    /// Enable or disable the check that calm winds have direction 0 and other winds do not, that
+    /// no level is much faster than the levels above and below it, and that the vector wind
+    /// shear between levels is plausible. Disabled by default.
+    pub fn with_wind_consistency_check(mut self, enabled: bool) -> Self {
+        self.wind_consistency = enabled;
+        self
+    }
+
+    /// Expect north winds to have direction 0 instead of 360, so 360 is an error and a wind with
+    /// direction 0 is not assumed to be calm. The validator only reads the sounding, so winds
+    /// with direction 360 are reported, not changed to 0. Use `normalize_north_winds` to change
+    /// them first. Defaults to false.
+    pub fn with_wind_north_as_zero(mut self, north_as_zero: bool) -> Self {
+        self.wind_north_as_zero = north_as_zero;
+        self
+    }
+
+    /// Set how much faster than both the level above and the level below a level may be before
+    /// it is a spike. Defaults to 30 knots.
+    pub fn with_wind_spike_tolerance<T>(mut self, tolerance: T) -> Self
+    where
+        Knots: From<T>,
+    {
+        self.wind_spike_tolerance = Knots::from(tolerance);
+        self
+    }
+
+    /// Set the largest magnitude of the vector wind shear allowed, per km. Defaults to 50 knots
+    /// per km.
+    pub fn with_max_wind_shear_per_km<T>(mut self, shear: T) -> Self
+    where
+        Knots: From<T>,
+    {
+        self.max_wind_shear_per_km = Knots::from(shear);
+        self
+    }
+
+    /// Set the minimum depth of the layers the wind shear is computed across. Levels closer
+    /// together than this are not compared to each other, so small differences between closely
+    /// spaced levels are not reported as large shears. Defaults to 300 m.
+    pub fn with_wind_shear_min_depth<T>(mut self, depth: T) -> Self
+    where
+        Meters: From<T>,
+    {
+        self.wind_shear_min_depth = Meters::from(depth);
+        self
+    }
+
+    /// Enable or disable the check that the cloud fraction profile and the low, mid, and high
+    /// cloud values are not negative and not more than 100%. The low, mid, and high cloud are
+    /// always fractions. Enabled by default.
+    pub fn with_cloud_check(mut self, enabled: bool) -> Self {
+        self.cloud = enabled;
+        self
+    }
+
+    /// Set the scale of the cloud fraction profile. With `CloudScale::Auto` the profile is a
+    /// percentage if most of its non-zero values are more than 1, and a fraction otherwise.
+    /// Defaults to `CloudScale::Auto`.
+    pub fn with_cloud_scale(mut self, scale: CloudScale) -> Self {
+        self.cloud_scale = scale;
+        self
+    }
+
+    /// Enable or disable the check that the heights are consistent with the pressure and
+    /// temperature profiles via the hypsometric equation. The virtual temperature is used when
+    /// the dew point is available. Disabled by default.
+    pub fn with_hydrostatic_check(mut self, enabled: bool) -> Self {
+        self.hydrostatic = enabled;
+        self
+    }
+
+    /// Set how far the thickness of a layer may differ from the thickness computed with the
+    /// hypsometric equation. Defaults to 50 m.
+    pub fn with_hydrostatic_tolerance<T>(mut self, tolerance: T) -> Self
+    where
+        Meters: From<T>,
+    {
+        self.hydrostatic_tolerance = Meters::from(tolerance);
+        self
+    }
+
+    /// Enable or disable the check that the equivalent potential temperature profile matches the
+    /// values computed from the pressure, temperature, and dew point. Disabled by default.
+    pub fn with_theta_e_check(mut self, enabled: bool) -> Self {
+        self.theta_e = enabled;
+        self
+    }
+
+    /// Set how far the stored equivalent potential temperature may differ from the computed
+    /// value. Defaults to 2 K to allow for sources that use a different formula.
+    pub fn with_theta_e_tolerance<T>(mut self, tolerance: T) -> Self
+    where
+        CelsiusDiff: From<T>,
+    {
+        self.theta_e_tolerance = CelsiusDiff::from(tolerance);
+        self
+    }
+
+    /// Enable or disable the check that the wet bulb profile matches the values computed from the
+    /// pressure, temperature, and dew point. Disabled by default.
+    pub fn with_wet_bulb_check(mut self, enabled: bool) -> Self {
+        self.wet_bulb = enabled;
+        self
+    }
+
+    /// Set how far the stored wet bulb temperature may differ from the computed value. Defaults
+    /// to 1 C.
+    pub fn with_wet_bulb_tolerance<T>(mut self, tolerance: T) -> Self
+    where
+        CelsiusDiff: From<T>,
+    {
+        self.wet_bulb_tolerance = CelsiusDiff::from(tolerance);
+        self
+    }
+
+    /// Enable or disable the check for layers where the potential temperature decreases with
+    /// height. In the surface layer, layers that exceed the tolerance but not the surface
+    /// tolerance are reported as warnings. Disabled by default.
+    pub fn with_superadiabatic_check(mut self, enabled: bool) -> Self {
+        self.superadiabatic = enabled;
+        self
+    }
+
+    /// Set how much the potential temperature may decrease across a layer. Defaults to 1 K.
+    pub fn with_superadiabatic_tolerance<T>(mut self, tolerance: T) -> Self
+    where
+        CelsiusDiff: From<T>,
+    {
+        self.superadiabatic_tolerance = CelsiusDiff::from(tolerance);
+        self
+    }
+
+    /// Set the depth of the surface layer, measured from the station pressure, where the
+    /// surface tolerance applies instead. Defaults to 50 hPa.
+    pub fn with_superadiabatic_surface_layer_depth<T>(mut self, depth: T) -> Self
+    where
+        HectoPascal: From<T>,
+    {
+        self.superadiabatic_surface_layer_depth = HectoPascal::from(depth);
+        self
+    }
+
+    /// Set how much the potential temperature may decrease across a layer in the surface layer.
+    /// Defaults to 3 K.
+    pub fn with_superadiabatic_surface_tolerance<T>(mut self, tolerance: T) -> Self
+    where
+        CelsiusDiff: From<T>,
+    {
+        self.superadiabatic_surface_tolerance = CelsiusDiff::from(tolerance);
+        self
+    }
+
+    /// Enable or disable the check that temperature, dew point, and wind speed are within
+    /// plausible limits for their pressure level. Disabled by default.
+    pub fn with_gross_limit_check(mut self, enabled: bool) -> Self {
+        self.gross_limits = enabled;
+        self
+    }
+
+    /// Set the table of limits used by the gross limit check. Defaults to
+    /// `GrossLimits::default()`.
+    pub fn with_gross_limits(mut self, limits: GrossLimits) -> Self {
+        self.gross_limits_table = limits;
+        self
+    }
+
+    /// Enable or disable the check that the MSLP is not negative. Enabled by default.
+    pub fn with_mslp_check(mut self, enabled: bool) -> Self {
+        self.mslp = enabled;
+        self
+    }
+
+    /// Enable or disable the check that the MSLP matches the station pressure reduced to sea level
+    /// using the station elevation and the surface temperature, and that it is not less than the
+    /// station pressure if the station is above sea level. Disabled by default.
+    pub fn with_mslp_reduction_check(mut self, enabled: bool) -> Self {
+        self.mslp_reduction = enabled;
+        self
+    }
+
+    /// Set how far the MSLP may differ from the reduced station pressure. Defaults to 5 hPa to
+    /// allow for sources that use a different reduction.
+    pub fn with_mslp_reduction_tolerance<T>(mut self, tolerance: T) -> Self
+    where
+        HectoPascal: From<T>,
+    {
+        self.mslp_reduction_tolerance = HectoPascal::from(tolerance);
+        self
+    }
+
+    /// Enable or disable the check that the station pressure is not negative. Enabled by default.
+    pub fn with_station_pressure_check(mut self, enabled: bool) -> Self {
+        self.station_pressure = enabled;
+        self
+    }
+
+    /// Enable or disable the check that the surface temperature is not less than the surface dew
+    /// point, using the temperature order tolerance, and that the precipitation is not negative.
+    /// Enabled by default.
+    pub fn with_surface_check(mut self, enabled: bool) -> Self {
+        self.surface = enabled;
+        self
+    }
+
+    /// Enable or disable the check that the surface temperature and dew point agree with the
+    /// lowest level of the profile when that level is at the station pressure. Disabled by
+    /// default.
+    pub fn with_surface_profile_check(mut self, enabled: bool) -> Self {
+        self.surface_profile = enabled;
+        self
+    }
+
+    /// Set how close to the station pressure the lowest level must be for the surface profile
+    /// check to compare them. Defaults to 1 hPa.
+    pub fn with_surface_profile_pressure_tolerance<T>(mut self, tolerance: T) -> Self
+    where
+        HectoPascal: From<T>,
+    {
+        self.surface_profile_pressure_tolerance = HectoPascal::from(tolerance);
+        self
+    }
+
+    /// Set how far the surface values may differ from the lowest level of the profile. Defaults
+    /// to 1 C.
+    pub fn with_surface_profile_tolerance<T>(mut self, tolerance: T) -> Self
+    where
+        CelsiusDiff: From<T>,
+    {
+        self.surface_profile_tolerance = CelsiusDiff::from(tolerance);
+        self
+    }
+
+    /// Enable or disable the check that the surface wind agrees with at least one of the lowest
+    /// levels of the wind profile. Directions are not compared when either wind is slower than
+    /// the minimum speed set with `with_surface_wind_direction_min_speed`. The surface wind speed
+    /// is also reported as a suspected unit error if it is within 10% of half or twice the speed
+    /// of every level, the ratio between m/s and knots. Disabled by default.
+    pub fn with_surface_wind_check(mut self, enabled: bool) -> Self {
+        self.surface_wind = enabled;
+        self
+    }
+
+    /// Set how many of the lowest levels with a wind the surface wind is compared to. Defaults
+    /// to 3.
+    pub fn with_surface_wind_levels(mut self, num_levels: usize) -> Self {
+        self.surface_wind_levels = num_levels;
+        self
+    }
+
+    /// Set how far the surface wind speed may differ from the profile. Defaults to 15 knots.
+    pub fn with_surface_wind_speed_tolerance<T>(mut self, tolerance: T) -> Self
+    where
+        Knots: From<T>,
+    {
+        self.surface_wind_speed_tolerance = Knots::from(tolerance);
+        self
+    }
+
+    /// Set how many degrees the surface wind direction may differ from the profile. Defaults to
+    /// 60 degrees.
+    pub fn with_surface_wind_direction_tolerance(mut self, degrees: f64) -> Self {
+        self.surface_wind_direction_tolerance = degrees;
+        self
+    }
+
+    /// Set how fast both the surface wind and a level must be for their directions to be
+    /// compared. Defaults to 15 knots.
+    pub fn with_surface_wind_direction_min_speed<T>(mut self, speed: T) -> Self
+    where
+        Knots: From<T>,
+    {
+        self.surface_wind_direction_min_speed = Knots::from(speed);
+        self
+    }
+
+    /// Enable or disable the check that the station number is not negative, the latitude is in
+    /// the range -90 to 90, the longitude is in the range -180 to 360, and the elevation is in
+    /// the range -500 to 9000 m. Enabled by default.
+    pub fn with_station_info_check(mut self, enabled: bool) -> Self {
+        self.station_info = enabled;
+        self
+    }
+
+    /// Enable or disable the check that the station elevation is not too far below the height of
+    /// the lowest level above the surface. Disabled by default.
+    pub fn with_station_elevation_check(mut self, enabled: bool) -> Self {
+        self.station_elevation = enabled;
+        self
+    }
+
+    /// Set how far the station elevation may be below the height of the lowest level. Defaults
+    /// to 100 m.
+    pub fn with_station_elevation_tolerance<T>(mut self, tolerance: T) -> Self
+    where
+        Meters: From<T>,
+    {
+        self.station_elevation_tolerance = Meters::from(tolerance);
+        self
+    }
+
+    /// Enable or disable the check that the lead time is not negative and not more than the
+    /// maximum lead time. Disabled by default.
+    pub fn with_lead_time_check(mut self, enabled: bool) -> Self {
+        self.lead_time = enabled;
+        self
+    }
+
+    /// Set the maximum lead time in hours. Defaults to 384 hours.
+    pub fn with_max_lead_time(mut self, hours: i32) -> Self {
+        self.max_lead_time = hours;
+        self
+    }
+
+    /// Check that the valid time is between `earliest` and `latest`, inclusive. For instance,
+    /// use the current time as `latest` to reject valid times in the future. Disabled until a
+    /// window is set.
+    pub fn with_valid_time_window(
+        mut self,
+        earliest: NaiveDateTime,
+        latest: NaiveDateTime,
+    ) -> Self {
+        self.valid_time_window = Some((earliest, latest));
+        self
+    }
+
+    /// Register a user defined check. Registered checks run in order after the built in checks.
+    pub fn with_check<C>(mut self, check: C) -> Self
+    where
+        C: Check + Send + Sync + 'static,
+    {
+        self.custom.0.push(Arc::new(check));
+        self
+    }
+
+    /// Set the lowest severity that causes `validate` to fail. Defaults to `Severity::Info`, so
+    /// any error fails validation.
+    pub fn with_failure_threshold(mut self, threshold: Severity) -> Self {
+        self.failure_threshold = threshold;
+        self
+    }
+
+    /// Run all the enabled checks and return every error found, regardless of severity.
+    pub fn run(&self, snd: &Sounding) -> ValidationErrors {
+        let mut err_return = ValidationErrors::new();
+
+        //
+        // Sounding checks
+        //
+
+        // Pressure required as vertical coordinate.
+        if self.pressure_profile {
+            err_return.push_error(check_pressure_exists(snd.pressure_profile()));
+        }
+
+        // Infinite values slip through or confuse the comparisons in the other checks.
+        if self.finite {
+            check_finite(snd, &mut err_return);
+        }
+
+        // Check for converter bugs
+        if self.units {
+            check_units(snd, self.cloud_scale, &mut err_return);
+        }
+
+        if self.vector_lengths {
+            check_vector_lengths(snd, &mut err_return);
+        }
+
+        // Check that pressure always decreases with height and that the station pressure is more
+        // than the lowest pressure level in sounding. AND..
+        // Check height always increases with height.
+        if self.vertical_order {
+            check_vertical_height_pressure(snd, &mut err_return);
+        }
+
+        // Check for gaps and too few levels
+        if self.vertical_resolution {
+            check_vertical_resolution(
+                snd,
+                self.max_pressure_gap,
+                self.max_height_gap,
+                self.min_levels,
+                &mut err_return,
+            );
+        }
+
+        // Check for missing data
+        check_missing_data(snd, &self.missing_data_thresholds, &mut err_return);
+
+        // Check the sounding is deep enough
+        if self.sounding_top {
+            check_sounding_top(snd, self.sounding_top_requirement, &mut err_return);
+        }
+
+        // Check the required levels are available
+        if self.mandatory_levels {
+            check_mandatory_levels(
+                snd,
+                &self.mandatory_levels_list,
+                self.mandatory_levels_spanned,
+                &mut err_return,
+            );
+        }
+
+        // Check for repeated levels
+        if self.duplicate_levels {
+            check_duplicate_levels(
+                snd,
+                self.duplicate_levels_epsilon,
+                self.duplicate_levels_tolerance,
+                self.duplicate_levels_wind_tolerance,
+                &mut err_return,
+            );
+        }
+
+        // Check that dew point <= wet bulb <= t
+        if self.temperature_order {
+            check_temp_wet_bulb_dew_point(snd, self.temperature_order_tolerance, &mut err_return);
+        }
+
+        // Check that speed >= 0 and direction 0-360
+        if self.wind {
+            check_wind(snd, &mut err_return);
+        }
+
+        // Check calm winds, speed spikes, and shear
+        if self.wind_consistency {
+            check_wind_consistency(
+                snd,
+                self.wind_north_as_zero,
+                self.wind_spike_tolerance,
+                self.max_wind_shear_per_km,
+                self.wind_shear_min_depth,
+                &mut err_return,
+            );
+        }
+
+        // Check that cloud fraction is between 0 and 100%
+        if self.cloud {
+            check_cloud(snd, self.cloud_scale, &mut err_return);
+        }
+
+        // Check the heights against the hypsometric equation
+        if self.hydrostatic {
+            check_hydrostatic(snd, self.hydrostatic_tolerance, &mut err_return);
+        }
+
+        // Recompute theta-e and compare to the stored profile
+        if self.theta_e {
+            check_theta_e(snd, self.theta_e_tolerance, &mut err_return);
+        }
+
+        // Recompute the wet bulb and compare to the stored profile
+        if self.wet_bulb {
+            check_wet_bulb(snd, self.wet_bulb_tolerance, &mut err_return);
+        }
+
+        // Check for potential temperature decreasing with height
+        if self.superadiabatic {
+            check_superadiabatic(
+                snd,
+                self.superadiabatic_tolerance,
+                self.superadiabatic_surface_layer_depth,
+                self.superadiabatic_surface_tolerance,
+                &mut err_return,
+            );
+        }
+
+        // Check values are within climatological limits
+        if self.gross_limits {
+            check_gross_limits(snd, &self.gross_limits_table, &mut err_return);
+        }
+
+        //
+        // Surface checks
+        //
+        if self.mslp {
+            check_mslp(snd, &mut err_return);
+        }
+
+        if self.mslp_reduction {
+            check_mslp_reduction(snd, self.mslp_reduction_tolerance, &mut err_return);
+        }
+
+        if self.station_pressure {
+            check_station_pressure(snd, &mut err_return);
+        }
+
+        if self.surface_wind {
+            check_surface_wind(
+                snd,
+                self.surface_wind_levels,
+                self.surface_wind_speed_tolerance,
+                self.surface_wind_direction_tolerance,
+                self.surface_wind_direction_min_speed,
+                &mut err_return,
+            );
+        }
+
+        if self.station_info {
+            check_station_info(snd, &mut err_return);
+        }
+
+        if self.station_elevation {
+            check_station_elevation(snd, self.station_elevation_tolerance, &mut err_return);
+        }
+
+        if self.surface {
+            check_surface(
+                snd,
+                self.temperature_order_tolerance,
+                self.temperature_order,
+                &mut err_return,
+            );
+        }
+
+        if self.surface_profile {
+            check_surface_profile(
+                snd,
+                self.surface_profile_pressure_tolerance,
+                self.surface_profile_tolerance,
+                &mut err_return,
+            );
+        }
+
+        //
+        // Time checks
+        //
+        if self.lead_time {
+            check_lead_time(snd, self.max_lead_time, &mut err_return);
+        }
+
+        if let Some(window) = self.valid_time_window {
+            check_valid_time(snd, window, &mut err_return);
+        }
+
+        //
+        // User defined checks
+        //
+        for check in &self.custom.0 {
+            check.check(snd, &mut err_return);
+        }
+
+        err_return
+    }
+
+    /// Run all the enabled checks and fail if any errors reach the failure threshold.
+    pub fn validate(&self, snd: &Sounding) -> Result<(), ValidationErrors> {
+        self.run(snd).check_severity(self.failure_threshold)
+    }
+}
+
+impl Check for Validator {
+    fn check(&self, snd: &Sounding, errors: &mut ValidationErrors) {
+        errors.append(self.run(snd));
+    }
+}
@@ -1,7 +1,12 @@
-use metfor::{Celsius, HectoPascal, Knots, Meters, WindSpdDir};
+use chrono::NaiveDate;
+use metfor::{Celsius, CelsiusDiff, HectoPascal, Kelvin, Knots, Meters, Mm, WindSpdDir};
 use optional::Optioned;
 use sounding_base::{Sounding, StationInfo};
-use sounding_validate::{validate, ValidationError};
+use sounding_validate::{
+    missing_data_stats, normalize_north_winds, validate, Check, CloudScale, GrossLimit,
+    GrossLimits, Level, MissingDataStats, MissingDataThreshold, Profile, Severity, ValidationError,
+    ValidationErrors, Validator,
+};
 
 #[test]
 fn test_validate() {
@@ -125,17 +130,120 @@ fn create_valid_test_sounding() -> Sounding {
 }
 
 #[test]
-fn test_pressure_not_decreasing_with_height() {
-    let snd = create_invalid_test_sounding_pressure_not_decreasing_with_height();
+fn test_station_pressure_less_than_first_level() {
+    let snd = create_invalid_test_sounding_station_pressure_less_than_first_level();
     let result = validate(&snd);
     assert!(result.is_err());
-    assert!(result
-        .unwrap_err()
-        .into_inner()
-        .contains(&ValidationError::PressureNotDecreasingWithHeight));
+    let errs = result.unwrap_err().into_inner();
+    assert_eq!(errs.len(), 1);
+
+    if let ValidationError::StationPressureLessThanFirstLevel(lvl, stn, val) = errs[0] {
+        assert_eq!(lvl.index, 1);
+        assert_eq!(lvl.pressure.unwrap(), HectoPascal(840.0));
+        assert_eq!(lvl.height.unwrap(), Meters(1050.0));
+        assert_eq!(stn, 830.0);
+        assert_eq!(val, 840.0);
+    } else {
+        panic!("Error is of wrong type!");
+    }
+}
+
+#[test]
+fn test_all_pressure_not_decreasing_with_height() {
+    let snd = create_valid_test_sounding().with_pressure_profile(vec![
+        Optioned::from(HectoPascal(840.0)),
+        Optioned::from(HectoPascal(800.0)),
+        Optioned::from(HectoPascal(850.0)),
+        Optioned::from(HectoPascal(500.0)),
+        Optioned::from(HectoPascal(600.0)),
+        Optioned::from(HectoPascal(250.0)),
+        Optioned::from(HectoPascal(200.0)),
+        Optioned::from(HectoPascal(100.0)),
+    ]);
+    let errs = validate(&snd).unwrap_err().into_inner();
+    println!("{:?}", errs);
+
+    let bad_levels: Vec<(usize, f64, f64)> = errs
+        .into_iter()
+        .filter_map(|err| match err {
+            ValidationError::PressureNotDecreasingWithHeight(lvl, below, val) => {
+                Some((lvl.index, below, val))
+            }
+            _ => None,
+        })
+        .collect();
+
+    assert_eq!(bad_levels, vec![(3, 800.0, 850.0), (5, 500.0, 600.0)]);
 }
 
-fn create_invalid_test_sounding_pressure_not_decreasing_with_height() -> Sounding {
+#[test]
+fn test_height_not_increasing_with_height() {
+    let snd = create_valid_test_sounding()
+        .with_station_info(StationInfo::new_with_values(
+            1,
+            (45.0, -115.0),
+            Meters(1100.0),
+        ))
+        .with_height_profile(vec![
+            Optioned::from(Meters(1050.0)),
+            Optioned::from(Meters(2000.0)),
+            Optioned::from(Meters(1900.0)),
+            Optioned::from(Meters(4000.0)),
+            Optioned::from(Meters(5000.0)),
+            Optioned::from(Meters(6500.0)),
+            Optioned::from(Meters(7000.0)),
+            Optioned::from(Meters(8000.0)),
+        ]);
+    let errs = validate(&snd).unwrap_err().into_inner();
+    println!("{:?}", errs);
+    assert_eq!(errs.len(), 2);
+
+    match errs[0] {
+        ValidationError::StationElevationAboveFirstHeight(lvl, elev, hgt) => {
+            assert_eq!(lvl.index, 1);
+            assert_eq!(elev, 1100.0);
+            assert_eq!(hgt, 1050.0);
+        }
+        _ => panic!("Error is of wrong type!"),
+    }
+
+    match errs[1] {
+        ValidationError::HeightNotIncreasingWithHeight(lvl, below, hgt) => {
+            assert_eq!(lvl.index, 3);
+            assert_eq!(below, 2000.0);
+            assert_eq!(hgt, 1900.0);
+        }
+        _ => panic!("Error is of wrong type!"),
+    }
+}
+
+#[test]
+fn test_severity_threshold() {
+    let mut errs = ValidationErrors::new();
+    errs.push_info(Err(ValidationError::NoPressureProfile));
+    errs.push_warning(Err(ValidationError::InvalidWindDirection(None, 400.0)));
+    errs.push_warning(Ok(()));
+
+    assert_eq!(errs.max_severity(), Some(Severity::Warning));
+
+    assert!(errs.clone().check_severity(Severity::Error).is_ok());
+    let errs = errs.check_severity(Severity::Warning).unwrap_err();
+    assert_eq!(errs.iter().count(), 2);
+
+    let sevs: Vec<Severity> = errs
+        .into_inner_with_severity()
+        .into_iter()
+        .map(|(sev, _)| sev)
+        .collect();
+    assert_eq!(sevs, vec![Severity::Info, Severity::Warning]);
+
+    // Everything found by validate is an error.
+    let snd = create_invalid_test_sounding_station_pressure_less_than_first_level();
+    let errs = validate(&snd).unwrap_err();
+    assert_eq!(errs.max_severity(), Some(Severity::Error));
+}
+
+fn create_invalid_test_sounding_station_pressure_less_than_first_level() -> Sounding {
     create_valid_test_sounding().with_station_pressure(HectoPascal(830.0))
 }
 
@@ -198,7 +306,7 @@ fn test_temperature_less_than_wetbulb() {
     assert!(errs.len() == 3);
 
     for err in errs {
-        if let ValidationError::TemperatureLessThanWetBulb(t, wb) = err {
+        if let ValidationError::TemperatureLessThanWetBulb(_, t, wb) = err {
             assert!(t < wb);
         } else {
             panic!("Error is of wrong type!");
@@ -206,6 +314,45 @@ fn test_temperature_less_than_wetbulb() {
     }
 }
 
+#[test]
+fn test_error_level_info() {
+    let snd = create_invalid_test_temperature_less_than_wetbulb();
+    let err = validate(&snd).unwrap_err();
+
+    let levels: Vec<Level> = err
+        .into_inner()
+        .into_iter()
+        .filter_map(|err| match err {
+            ValidationError::TemperatureLessThanWetBulb(lvl, _, _) => Some(lvl),
+            _ => None,
+        })
+        .collect();
+
+    let indexes: Vec<usize> = levels.iter().map(|lvl| lvl.index).collect();
+    assert_eq!(indexes, vec![2, 3, 4]);
+
+    assert_eq!(levels[0].pressure.unwrap(), HectoPascal(800.0));
+    assert_eq!(levels[0].height.unwrap(), Meters(2000.0));
+    assert_eq!(levels[2].pressure.unwrap(), HectoPascal(500.0));
+    assert_eq!(levels[2].height.unwrap(), Meters(4000.0));
+
+    assert_eq!(format!("{}", levels[1]), "level 3 (700 hPa, 3000 m)");
+}
+
+#[test]
+fn test_level_equality_with_missing_values() {
+    let snd = create_valid_test_sounding().with_height_profile(vec![]);
+    let lvl = Level::from_sounding(&snd, 2);
+    assert!(lvl.height.is_none());
+    assert_eq!(lvl, lvl);
+    assert_eq!(lvl, Level::from_sounding(&snd, 2));
+    assert_ne!(lvl, Level::from_sounding(&snd, 3));
+
+    let lvl = Level::from_sounding(&Sounding::new(), 0);
+    assert!(lvl.pressure.is_none());
+    assert_eq!(lvl, lvl);
+}
+
 fn create_invalid_test_temperature_less_than_wetbulb() -> Sounding {
     let t_profile = vec![
         Optioned::from(Celsius(20.0)),
@@ -232,9 +379,9 @@ fn test_temperature_less_than_dewpoint() {
     assert!(errs.len() == 6);
 
     for err in errs {
-        if let ValidationError::TemperatureLessThanDewPoint(t, dp) = err {
+        if let ValidationError::TemperatureLessThanDewPoint(_, t, dp) = err {
             assert!(t < dp);
-        } else if let ValidationError::TemperatureLessThanWetBulb(t, wb) = err {
+        } else if let ValidationError::TemperatureLessThanWetBulb(_, t, wb) = err {
             assert!(t < wb);
         } else {
             panic!("Error is of wrong type!");
@@ -268,7 +415,7 @@ fn test_wetbulb_less_than_dewpoint() {
     assert!(errs.len() == 3);
 
     for err in errs {
-        if let ValidationError::WetBulbLessThanDewPoint(wb, dp) = err {
+        if let ValidationError::WetBulbLessThanDewPoint(_, wb, dp) = err {
             assert!(wb < dp);
         } else {
             panic!("Error is of wrong type!");
@@ -302,10 +449,10 @@ fn test_invalid_negative_value() {
     assert!(errs.len() == 9);
 
     for err in errs {
-        if let ValidationError::InvalidNegativeValue(_, val) = err {
-            assert!(val < 0.0);
-        } else {
-            assert!(err == ValidationError::PressureNotDecreasingWithHeight);
+        match err {
+            ValidationError::InvalidNegativeValue(_, _, val) => assert!(val < 0.0),
+            ValidationError::StationPressureLessThanFirstLevel(..) => {}
+            _ => panic!("Error is of wrong type!"),
         }
     }
 }
@@ -378,8 +525,8 @@ fn test_invalid_wind_direction() {
     assert_eq!(errs.len(), 4);
 
     for err in errs {
-        if let ValidationError::InvalidWindDirection(val) = err {
-            assert!(val < 0.0 || val > 360.0);
+        if let ValidationError::InvalidWindDirection(_, val) = err {
+            assert!(!(0.0..=360.0).contains(&val));
         } else {
             panic!("Error is of wrong type!");
         }
@@ -429,3 +576,1399 @@ fn create_invalid_test_wind_direction() -> Sounding {
             speed: Knots(5.0),
         })
 }
+
+#[test]
+fn test_validator_disable_checks() {
+    let snd = create_invalid_test_wind_direction();
+    assert!(Validator::new().validate(&snd).is_err());
+    assert!(Validator::new()
+        .with_wind_check(false)
+        .validate(&snd)
+        .is_ok());
+
+    let snd = create_invalid_test_invalid_negative_value();
+    let validator = Validator::new()
+        .with_wind_check(false)
+        .with_cloud_check(false)
+        .with_mslp_check(false)
+        .with_station_pressure_check(false)
+        .with_vertical_order_check(false);
+    assert!(validator.validate(&snd).is_ok());
+}
+
+#[test]
+fn test_validator_temperature_order_tolerance() {
+    let snd = create_invalid_test_temperature_less_than_wetbulb();
+    let validator = Validator::new().with_temperature_order_tolerance(CelsiusDiff(1.0));
+    assert!(validator.validate(&snd).is_ok());
+
+    let snd = create_invalid_test_temperature_less_than_dewpoint();
+    let errs = validator.validate(&snd).unwrap_err().into_inner();
+    assert_eq!(errs.len(), 4);
+
+    // Only the temperature 3C below the dew point exceeds the tolerance.
+    let dp_errs: Vec<_> = errs
+        .iter()
+        .filter(|err| matches!(err, ValidationError::TemperatureLessThanDewPoint(..)))
+        .collect();
+    assert_eq!(dp_errs.len(), 1);
+}
+
+#[test]
+fn test_custom_checks() {
+    // Site specific rule flagging any dew point below -70C.
+    let dew_point_cutoff = |snd: &Sounding, errs: &mut ValidationErrors| {
+        for (i, dp) in snd.dew_point_profile().iter().enumerate() {
+            if let Some(Celsius(dp)) = dp.into_option() {
+                if dp < -70.0 {
+                    errs.push_warning(Err(ValidationError::Custom(
+                        "DP_CUTOFF",
+                        Some(Level::from_sounding(snd, i)),
+                        format!("dew point {} below cutoff", dp),
+                    )));
+                }
+            }
+        }
+    };
+
+    let snd = create_valid_test_sounding();
+    let validator = Validator::new().with_check(dew_point_cutoff);
+
+    let errs = validator.validate(&snd).unwrap_err();
+    println!("{}", errs);
+    assert_eq!(errs.max_severity(), Some(Severity::Warning));
+
+    let errs = errs.into_inner();
+    assert_eq!(errs.len(), 1);
+    if let ValidationError::Custom(id, Some(lvl), _) = &errs[0] {
+        assert_eq!(*id, "DP_CUTOFF");
+        assert_eq!(lvl.index, 8);
+        assert_eq!(lvl.pressure.unwrap(), HectoPascal(100.0));
+    } else {
+        panic!("Error is of wrong type!");
+    }
+
+    // Only fail on errors, so the warning passes.
+    let validator = validator.with_failure_threshold(Severity::Error);
+    assert!(validator.validate(&snd).is_ok());
+
+    // A validator is itself a check.
+    let mut errs = ValidationErrors::new();
+    Validator::new().check(&create_invalid_test_wind_direction(), &mut errs);
+    assert_eq!(errs.into_inner().len(), 4);
+}
+
+fn create_hydrostatic_test_sounding() -> Sounding {
+    // Heights computed with the hypsometric equation from the temperatures.
+    Sounding::new()
+        .with_station_info(StationInfo::new_with_values(
+            1,
+            (45.0, -115.0),
+            Meters(100.0),
+        ))
+        .with_station_pressure(HectoPascal(1000.0))
+        .with_sfc_temperature(Celsius(20.0))
+        .with_pressure_profile(
+            vec![
+                975.0, 925.0, 850.0, 700.0, 500.0, 400.0, 300.0, 250.0, 200.0,
+            ]
+            .into_iter()
+            .map(HectoPascal)
+            .map(Optioned::from)
+            .collect(),
+        )
+        .with_temperature_profile(
+            vec![18.0, 15.0, 10.0, 0.0, -15.0, -25.0, -40.0, -50.0, -55.0]
+                .into_iter()
+                .map(Celsius)
+                .map(Optioned::from)
+                .collect(),
+        )
+        .with_height_profile(
+            vec![
+                317.0, 763.0, 1470.0, 3051.0, 5667.0, 7321.0, 9347.0, 10565.0, 12006.0,
+            ]
+            .into_iter()
+            .map(Meters)
+            .map(Optioned::from)
+            .collect(),
+        )
+}
+
+#[test]
+fn test_hydrostatic_check() {
+    let validator = Validator::new().with_hydrostatic_check(true);
+
+    let snd = create_hydrostatic_test_sounding();
+    let result = validator.validate(&snd);
+    if result.is_err() {
+        println!("{:?}", result);
+    }
+    assert!(result.is_ok());
+
+    // Corrupt the 500 hPa height, both layers it bounds are bad.
+    let mut heights = snd.height_profile()[1..].to_vec();
+    heights[4] = Optioned::from(Meters(5500.0));
+    let snd = snd.with_height_profile(heights);
+
+    let errs = validator.validate(&snd).unwrap_err();
+    println!("{}", errs);
+
+    let layers: Vec<(usize, usize)> = errs
+        .into_inner()
+        .into_iter()
+        .map(|err| match err {
+            ValidationError::HydrostaticInconsistency(bottom, top, computed, reported) => {
+                assert!((computed - reported).abs() > 50.0);
+                (bottom.index, top.index)
+            }
+            _ => panic!("Error is of wrong type!"),
+        })
+        .collect();
+    assert_eq!(layers, vec![(4, 5), (5, 6)]);
+
+    // Not checked by default.
+    assert!(validate(&snd).is_ok());
+
+    // With a big enough tolerance it passes.
+    let validator = validator.with_hydrostatic_tolerance(Meters(200.0));
+    assert!(validator.validate(&snd).is_ok());
+}
+
+#[test]
+fn test_theta_e_check() {
+    let snd = create_valid_test_sounding();
+
+    // Skip the surface values at index 0.
+    let theta_e: Vec<Optioned<Kelvin>> = snd
+        .pressure_profile()
+        .iter()
+        .zip(snd.temperature_profile())
+        .zip(snd.dew_point_profile())
+        .skip(1)
+        .map(|((p, t), dp)| {
+            Optioned::from(metfor::theta_e(t.unwrap(), dp.unwrap(), p.unwrap()).unwrap())
+        })
+        .collect();
+
+    let validator = Validator::new().with_theta_e_check(true);
+
+    let good_snd = snd.clone().with_theta_e_profile(theta_e.clone());
+    assert!(validator.validate(&good_snd).is_ok());
+
+    // Stored in Celsius instead of Kelvin.
+    let celsius: Vec<Optioned<Kelvin>> = theta_e
+        .iter()
+        .map(|val| val.map_t(|Kelvin(val)| Kelvin(val - 273.15)))
+        .collect();
+    let bad_snd = snd.clone().with_theta_e_profile(celsius);
+
+    let errs = validator.validate(&bad_snd).unwrap_err();
+    println!("{}", errs);
+
+    let errs = errs.into_inner();
+    assert_eq!(errs.len(), 8);
+    for err in errs {
+        if let ValidationError::ThetaEMismatch(lvl, stored, computed) = err {
+            assert!(lvl.index > 0);
+            assert!((stored - computed).abs() > 2.0);
+        } else {
+            panic!("Error is of wrong type!");
+        }
+    }
+
+    // Not checked by default.
+    assert!(validate(&bad_snd).is_ok());
+}
+
+#[test]
+fn test_wet_bulb_check() {
+    let validator = Validator::new()
+        .with_wet_bulb_check(true)
+        .with_wet_bulb_tolerance(CelsiusDiff(0.5));
+
+    // Using the dew point for the wet bulb passes the ordering checks, but is wrong.
+    let snd = create_valid_test_sounding();
+    let dew_point = snd.dew_point_profile()[1..].to_vec();
+    let bad_snd = snd.clone().with_wet_bulb_profile(dew_point);
+    assert!(validate(&bad_snd).is_ok());
+
+    let errs = validator.validate(&bad_snd).unwrap_err();
+    println!("{}", errs);
+
+    // Levels where the wet bulb cannot be computed are skipped.
+    let levels: Vec<usize> = errs
+        .into_inner()
+        .into_iter()
+        .map(|err| match err {
+            ValidationError::WetBulbMismatch(lvl, stored, computed) => {
+                assert!((stored - computed).abs() > 0.5);
+                lvl.index
+            }
+            _ => panic!("Error is of wrong type!"),
+        })
+        .collect();
+    assert_eq!(levels, vec![2, 3, 4]);
+
+    // Skip the surface values at index 0.
+    let wet_bulb: Vec<Optioned<Celsius>> = snd
+        .pressure_profile()
+        .iter()
+        .zip(snd.temperature_profile())
+        .zip(snd.dew_point_profile())
+        .skip(1)
+        .map(|((p, t), dp)| Optioned::from(metfor::wet_bulb(t.unwrap(), dp.unwrap(), p.unwrap())))
+        .collect();
+
+    let snd = snd.with_wet_bulb_profile(wet_bulb);
+    assert!(validator.validate(&snd).is_ok());
+}
+
+#[test]
+fn test_superadiabatic_check() {
+    let validator = Validator::new().with_superadiabatic_check(true);
+
+    let snd = create_hydrostatic_test_sounding();
+    assert!(validator.validate(&snd).is_ok());
+
+    // Slightly superadiabatic near the surface and a strongly superadiabatic layer aloft.
+    let snd = snd.with_temperature_profile(
+        vec![15.0, 15.0, 10.0, 0.0, -15.0, -5.0, -40.0, -50.0, -55.0]
+            .into_iter()
+            .map(Celsius)
+            .map(Optioned::from)
+            .collect(),
+    );
+
+    let errs = validator.run(&snd);
+    println!("{}", errs);
+
+    let layers: Vec<(Severity, usize, usize)> = errs
+        .into_inner_with_severity()
+        .into_iter()
+        .map(|(sev, err)| match err {
+            ValidationError::SuperadiabaticLapseRate(bottom, top, theta_bottom, theta_top) => {
+                assert!(theta_bottom > theta_top);
+                (sev, bottom.index, top.index)
+            }
+            _ => panic!("Error is of wrong type!"),
+        })
+        .collect();
+    assert_eq!(
+        layers,
+        vec![(Severity::Warning, 0, 1), (Severity::Error, 6, 7)]
+    );
+
+    // With a shallower surface layer the first layer is an error too.
+    let errs = validator
+        .with_superadiabatic_surface_layer_depth(HectoPascal(10.0))
+        .run(&snd);
+    assert_eq!(
+        errs.iter()
+            .filter(|(sev, _)| *sev == Severity::Error)
+            .count(),
+        2
+    );
+
+    // Not checked by default.
+    assert!(validate(&snd).is_ok());
+}
+
+#[test]
+fn test_gross_limits() {
+    let limits = GrossLimits::default();
+    let limit_500 = limits.limits_at(HectoPascal(500.0)).unwrap();
+    let limit_400 = limits.limits_at(HectoPascal(400.0)).unwrap();
+    let limit_300 = limits.limits_at(HectoPascal(300.0)).unwrap();
+    assert!(limit_400.max_temperature < limit_500.max_temperature);
+    assert!(limit_400.max_temperature > limit_300.max_temperature);
+
+    // Beyond the ends of the table
+    assert_eq!(
+        limits
+            .limits_at(HectoPascal(1085.0))
+            .unwrap()
+            .max_temperature,
+        limits
+            .limits_at(HectoPascal(1050.0))
+            .unwrap()
+            .max_temperature
+    );
+    assert!(GrossLimits::new(vec![])
+        .limits_at(HectoPascal(500.0))
+        .is_none());
+
+    // A winter sounding from the South Pole.
+    let limit_680 = limits.limits_at(HectoPascal(680.0)).unwrap();
+    assert!(limit_680.min_temperature < Celsius(-66.0));
+    assert!(limit_680.min_dew_point < Celsius(-70.0));
+}
+
+#[test]
+fn test_gross_limit_check() {
+    let validator = Validator::new().with_gross_limit_check(true);
+
+    let snd = create_valid_test_sounding();
+    assert!(validator.validate(&snd).is_ok());
+
+    let mut temperature = snd.temperature_profile()[1..].to_vec();
+    temperature[3] = Optioned::from(Celsius(40.0));
+    let mut wind = snd.wind_profile()[1..].to_vec();
+    wind[7] = Optioned::from(WindSpdDir {
+        direction: 280.0,
+        speed: Knots(400.0),
+    });
+    let snd = snd
+        .with_temperature_profile(temperature)
+        .with_wind_profile(wind);
+
+    let errs = validator.validate(&snd).unwrap_err();
+    println!("{}", errs);
+
+    let errs: Vec<_> = errs
+        .into_inner()
+        .into_iter()
+        .filter_map(|err| match err {
+            ValidationError::GrossLimitExceeded(name, lvl, val, bound) => {
+                Some((name, lvl.index, val, bound))
+            }
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        errs,
+        vec![
+            ("Temperature", 4, 40.0, 10.0),
+            ("Wind speed", 8, 400.0, 250.0)
+        ]
+    );
+
+    // Override the table with something stricter.
+    let strict = GrossLimits::new(vec![GrossLimit::new(
+        HectoPascal(1000.0),
+        (Celsius(-100.0), Celsius(50.0)),
+        (Celsius(-120.0), Celsius(50.0)),
+        Knots(50.0),
+    )]);
+    let snd = create_valid_test_sounding();
+    let errs = validator
+        .with_gross_limits(strict)
+        .validate(&snd)
+        .unwrap_err();
+    assert_eq!(errs.into_inner().len(), 2); // 62 and 80 knots
+}
+
+#[test]
+fn test_cloud_out_of_range() {
+    let snd = create_valid_test_sounding();
+    let mut cloud = snd.cloud_fraction_profile()[1..].to_vec();
+    cloud[2] = Optioned::from(250.0);
+    let snd = snd.with_cloud_fraction_profile(cloud);
+
+    let errs = validate(&snd).unwrap_err().into_inner();
+    assert_eq!(errs.len(), 1);
+    match &errs[0] {
+        ValidationError::OutOfRange(name, Some(lvl), val, min, max) => {
+            assert_eq!(*name, "Cloud fraction");
+            assert_eq!(lvl.index, 3);
+            assert_eq!((*val, *min, *max), (250.0, 0.0, 100.0));
+        }
+        err => panic!("Error is of wrong type: {}", err),
+    }
+
+    // The same profile as fractions is fine.
+    let snd = create_valid_test_sounding();
+    let cloud: Vec<_> = snd.cloud_fraction_profile()[1..]
+        .iter()
+        .map(|cld| cld.map_t(|cld| cld / 100.0))
+        .collect();
+    let snd = snd
+        .with_cloud_fraction_profile(cloud)
+        .with_low_cloud(0.5)
+        .with_mid_cloud(1.0);
+    assert!(validate(&snd).is_ok());
+
+    // A bad value in a fraction profile does not make it look like a percentage.
+    let mut cloud: Vec<_> = snd.cloud_fraction_profile()[1..].to_vec();
+    cloud[2] = Optioned::from(2.5);
+    let snd = snd.with_cloud_fraction_profile(cloud);
+    let errs = validate(&snd).unwrap_err().into_inner();
+    assert_eq!(
+        errs,
+        vec![ValidationError::OutOfRange(
+            "Cloud fraction",
+            Some(Level::from_sounding(&snd, 3)),
+            2.5,
+            0.0,
+            1.0
+        )]
+    );
+
+    // Unless the scale is set.
+    assert!(Validator::new()
+        .with_cloud_scale(CloudScale::Percent)
+        .validate(&snd)
+        .is_ok());
+}
+
+#[test]
+fn test_surface_check() {
+    let snd = Sounding::new()
+        .with_pressure_profile(vec![
+            Optioned::from(HectoPascal(850.0)),
+            Optioned::from(HectoPascal(700.0)),
+        ])
+        .with_station_pressure(HectoPascal(860.0))
+        .with_sfc_temperature(Celsius(10.0))
+        .with_sfc_dew_point(Celsius(15.0))
+        .with_precipitation(Mm(-1.0));
+
+    let errs = validate(&snd).unwrap_err().into_inner();
+    assert_eq!(
+        errs,
+        vec![
+            ValidationError::SurfaceTemperatureLessThanDewPoint(10.0, 15.0),
+            ValidationError::InvalidNegativeValue("Precipitation", None, -1.0),
+        ]
+    );
+
+    assert!(Validator::new()
+        .with_surface_check(false)
+        .validate(&snd)
+        .is_ok());
+
+    // With profiles the surface is reported by the temperature order check, or by the surface
+    // check when that is disabled.
+    let snd = create_valid_test_sounding()
+        .with_sfc_temperature(Celsius(10.0))
+        .with_sfc_dew_point(Celsius(15.0));
+    let errs = validate(&snd).unwrap_err().into_inner();
+    assert!(errs.contains(&ValidationError::TemperatureLessThanDewPoint(
+        Level::from_sounding(&snd, 0),
+        10.0,
+        15.0
+    )));
+    assert!(
+        !errs.contains(&ValidationError::SurfaceTemperatureLessThanDewPoint(
+            10.0, 15.0
+        ))
+    );
+
+    let errs = Validator::new()
+        .with_temperature_order_check(false)
+        .validate(&snd)
+        .unwrap_err()
+        .into_inner();
+    assert_eq!(
+        errs,
+        vec![ValidationError::SurfaceTemperatureLessThanDewPoint(
+            10.0, 15.0
+        )]
+    );
+}
+
+#[test]
+fn test_surface_profile_check() {
+    let validator = Validator::new().with_surface_profile_check(true);
+
+    // The lowest level is 7 hPa above the station, so it is not compared by default.
+    let snd = create_valid_test_sounding()
+        .with_sfc_temperature(Celsius(25.0))
+        .with_sfc_dew_point(Celsius(13.0));
+    assert!(validator.validate(&snd).is_ok());
+
+    let errs = validator
+        .with_surface_profile_pressure_tolerance(HectoPascal(10.0))
+        .validate(&snd)
+        .unwrap_err();
+    println!("{}", errs);
+
+    let errs = errs.into_inner();
+    assert_eq!(errs.len(), 2);
+    match &errs[0] {
+        ValidationError::SurfaceProfileMismatch(name, lvl, sfc, val) => {
+            assert_eq!(*name, "Temperature");
+            assert_eq!(lvl.index, 1);
+            assert_eq!((*sfc, *val), (25.0, 20.0));
+        }
+        err => panic!("Error is of wrong type: {}", err),
+    }
+    match &errs[1] {
+        ValidationError::SurfaceProfileMismatch(name, _, sfc, val) => {
+            assert_eq!(*name, "Dew point");
+            assert_eq!((*sfc, *val), (13.0, 20.0));
+        }
+        err => panic!("Error is of wrong type: {}", err),
+    }
+}
+
+#[test]
+fn test_mslp_reduction_check() {
+    let validator = Validator::new().with_mslp_reduction_check(true);
+
+    // 1000 hPa at 100 m and 20C reduces to about 1011.7 hPa.
+    let snd = create_hydrostatic_test_sounding().with_mslp(HectoPascal(1012.0));
+    assert!(validator.validate(&snd).is_ok());
+
+    let snd = snd.with_mslp(HectoPascal(1020.0));
+    let errs = validator.validate(&snd).unwrap_err().into_inner();
+    assert_eq!(errs.len(), 1);
+    match errs[0] {
+        ValidationError::MslpMismatch(mslp, computed) => {
+            assert_eq!(mslp, 1020.0);
+            assert!((computed - 1011.7).abs() < 0.1);
+        }
+        ref err => panic!("Error is of wrong type: {}", err),
+    }
+    assert!(validator
+        .clone()
+        .with_mslp_reduction_tolerance(HectoPascal(10.0))
+        .validate(&snd)
+        .is_ok());
+
+    let snd = snd.with_mslp(HectoPascal(995.0));
+    let errs = validator.validate(&snd).unwrap_err().into_inner();
+    assert_eq!(
+        errs,
+        vec![ValidationError::MslpLessThanStationPressure(995.0, 1000.0)]
+    );
+}
+
+#[test]
+fn test_station_info_check() {
+    // Latitude and longitude swapped.
+    let snd = create_valid_test_sounding().with_station_info(StationInfo::new_with_values(
+        -1,
+        (-115.0, 45.0),
+        Meters(9500.0),
+    ));
+
+    let errs = validate(&snd).unwrap_err();
+    println!("{}", errs);
+    let errs: Vec<_> = errs
+        .into_inner()
+        .into_iter()
+        .filter(|err| !matches!(err, ValidationError::StationElevationAboveFirstHeight(..)))
+        .collect();
+    assert_eq!(
+        errs,
+        vec![
+            ValidationError::InvalidNegativeValue("Station number", None, -1.0),
+            ValidationError::OutOfRange("Latitude", None, -115.0, -90.0, 90.0),
+            ValidationError::OutOfRange("Station elevation", None, 9500.0, -500.0, 9000.0),
+        ]
+    );
+
+    let snd = create_valid_test_sounding().with_station_info(StationInfo::new_with_values(
+        1,
+        (45.0, 245.0),
+        Meters(1023.0),
+    ));
+    assert!(validate(&snd).is_ok());
+
+    let snd = create_valid_test_sounding().with_station_info(StationInfo::new_with_values(
+        1,
+        (45.0, 360.0),
+        Meters(1023.0),
+    ));
+    assert!(validate(&snd).is_err());
+    assert!(Validator::new()
+        .with_station_info_check(false)
+        .validate(&snd)
+        .is_ok());
+}
+
+#[test]
+fn test_time_checks() {
+    let validator = Validator::new()
+        .with_lead_time_check(true)
+        .with_max_lead_time(48);
+
+    let snd = create_valid_test_sounding().with_lead_time(36);
+    assert!(validator.validate(&snd).is_ok());
+
+    let snd = snd.with_lead_time(-6);
+    let errs = validator.validate(&snd).unwrap_err().into_inner();
+    assert_eq!(
+        errs,
+        vec![ValidationError::OutOfRange(
+            "Lead time",
+            None,
+            -6.0,
+            0.0,
+            48.0
+        )]
+    );
+
+    let snd = snd.with_lead_time(72);
+    assert!(validator.validate(&snd).is_err());
+
+    let start = NaiveDate::from_ymd_opt(2019, 5, 1)
+        .unwrap()
+        .and_hms_opt(0, 0, 0)
+        .unwrap();
+    let end = NaiveDate::from_ymd_opt(2019, 5, 2)
+        .unwrap()
+        .and_hms_opt(0, 0, 0)
+        .unwrap();
+    let validator = Validator::new().with_valid_time_window(start, end);
+
+    // No valid time is not an error.
+    let snd = create_valid_test_sounding();
+    assert!(validator.validate(&snd).is_ok());
+
+    let vt = NaiveDate::from_ymd_opt(2019, 5, 1)
+        .unwrap()
+        .and_hms_opt(12, 0, 0)
+        .unwrap();
+    let snd = snd.with_valid_time(vt);
+    assert!(validator.validate(&snd).is_ok());
+
+    let vt = NaiveDate::from_ymd_opt(2019, 5, 3)
+        .unwrap()
+        .and_hms_opt(12, 0, 0)
+        .unwrap();
+    let snd = snd.with_valid_time(vt);
+    let errs = validator.validate(&snd).unwrap_err().into_inner();
+    assert_eq!(
+        errs,
+        vec![ValidationError::ValidTimeOutsideWindow(vt, start, end)]
+    );
+}
+
+#[test]
+fn test_non_finite_values() {
+    let snd = create_valid_test_sounding();
+    let mut temperature = snd.temperature_profile()[1..].to_vec();
+    temperature[2] = Optioned::from(Celsius(f64::INFINITY));
+    let snd = snd
+        .with_temperature_profile(temperature)
+        .with_mslp(HectoPascal(f64::NEG_INFINITY))
+        .with_station_info(StationInfo::new_with_values(
+            1,
+            (f64::NAN, -115.0),
+            Meters(1023.0),
+        ));
+
+    let errs = validate(&snd).unwrap_err();
+    println!("{}", errs);
+
+    let errs: Vec<_> = errs
+        .into_inner()
+        .into_iter()
+        .filter_map(|err| match err {
+            ValidationError::NonFiniteValue(name, lvl, _) => Some((name, lvl.map(|l| l.index))),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        errs,
+        vec![("Temperature", Some(3)), ("MSLP", None), ("Latitude", None)]
+    );
+
+    // NaN is just a missing value in the profiles.
+    let snd = create_valid_test_sounding();
+    let mut temperature = snd.temperature_profile()[1..].to_vec();
+    temperature[2] = Optioned::from(Celsius(f64::NAN));
+    let snd = snd.with_temperature_profile(temperature);
+    assert!(validate(&snd).is_ok());
+}
+
+#[test]
+fn test_duplicate_level_check() {
+    let make_sounding = |pressure: [f64; 4], temperature: [f64; 4]| {
+        Sounding::new()
+            .with_pressure_profile(
+                pressure
+                    .iter()
+                    .map(|&p| Optioned::from(HectoPascal(p)))
+                    .collect(),
+            )
+            .with_temperature_profile(
+                temperature
+                    .iter()
+                    .map(|&t| Optioned::from(Celsius(t)))
+                    .collect(),
+            )
+            .with_station_pressure(HectoPascal(850.0))
+            .with_sfc_temperature(Celsius(10.0))
+    };
+    let validator = Validator::new().with_duplicate_level_check(true);
+
+    // The surface repeats the first level, which is not a duplicate.
+    let snd = make_sounding([850.0, 700.0, 500.0, 300.0], [10.0, 0.0, -10.0, -30.0]);
+    assert!(validator.validate(&snd).is_ok());
+
+    let snd = make_sounding([850.0, 700.0, 700.0, 500.0], [10.0, 0.0, 0.0, -10.0]);
+    let errs = validator.validate(&snd).unwrap_err();
+    println!("{}", errs);
+    assert_eq!(errs.max_severity(), Some(Severity::Warning));
+    assert_eq!(
+        errs.into_inner(),
+        vec![ValidationError::DuplicateLevel(
+            Level::from_sounding(&snd, 2),
+            Level::from_sounding(&snd, 3)
+        )]
+    );
+
+    let snd = make_sounding([850.0, 700.0, 699.95, 500.0], [10.0, 0.0, 1.0, -10.0]);
+    assert!(validator.validate(&snd).is_ok());
+
+    let errs = validator
+        .with_duplicate_level_epsilon(HectoPascal(0.1))
+        .validate(&snd)
+        .unwrap_err();
+    println!("{}", errs);
+    assert_eq!(errs.max_severity(), Some(Severity::Error));
+    assert_eq!(
+        errs.into_inner(),
+        vec![ValidationError::ConflictingDuplicateLevel(
+            Level::from_sounding(&snd, 2),
+            Level::from_sounding(&snd, 3)
+        )]
+    );
+
+    // Near-duplicates with slightly different heights and temperatures agree.
+    let snd = make_sounding([850.0, 700.0, 699.95, 500.0], [10.0, 0.0, 0.1, -10.0])
+        .with_height_profile(vec![
+            Optioned::from(Meters(1500.0)),
+            Optioned::from(Meters(3000.0)),
+            Optioned::from(Meters(3000.5)),
+            Optioned::from(Meters(5500.0)),
+        ]);
+    let errs = Validator::new()
+        .with_duplicate_level_check(true)
+        .with_duplicate_level_epsilon(HectoPascal(0.1))
+        .validate(&snd)
+        .unwrap_err();
+    assert_eq!(
+        errs.into_inner(),
+        vec![ValidationError::DuplicateLevel(
+            Level::from_sounding(&snd, 2),
+            Level::from_sounding(&snd, 3)
+        )]
+    );
+
+    // Identical pressures must have identical heights.
+    let snd = make_sounding([850.0, 700.0, 700.0, 500.0], [10.0, 0.0, 0.0, -10.0])
+        .with_height_profile(vec![
+            Optioned::from(Meters(1500.0)),
+            Optioned::from(Meters(3000.0)),
+            Optioned::from(Meters(3000.5)),
+            Optioned::from(Meters(5500.0)),
+        ]);
+    let errs = Validator::new()
+        .with_duplicate_level_check(true)
+        .validate(&snd)
+        .unwrap_err();
+    assert_eq!(
+        errs.into_inner(),
+        vec![ValidationError::ConflictingDuplicateLevel(
+            Level::from_sounding(&snd, 2),
+            Level::from_sounding(&snd, 3)
+        )]
+    );
+}
+
+#[test]
+fn test_vertical_resolution_check() {
+    let snd = create_valid_test_sounding();
+    let validator = Validator::new().with_vertical_resolution_check(true);
+
+    let errs = validator.validate(&snd).unwrap_err();
+    println!("{}", errs);
+    let gaps: Vec<_> = errs
+        .into_inner()
+        .into_iter()
+        .map(|err| match err {
+            ValidationError::PressureGap(lvl_a, lvl_b, gap) => (lvl_a.index, lvl_b.index, gap),
+            err => panic!("Error is of wrong type: {}", err),
+        })
+        .collect();
+    assert_eq!(gaps, vec![(3, 4, 200.0), (4, 5, 200.0)]);
+
+    let validator = validator
+        .with_max_pressure_gap(HectoPascal(250.0))
+        .with_max_height_gap(Meters(1000.0))
+        .with_min_levels(9);
+    let errs = validator.validate(&snd).unwrap_err().into_inner();
+    assert_eq!(errs.len(), 2);
+    assert_eq!(errs[0], ValidationError::TooFewLevels(8, 9));
+    assert_eq!(
+        errs[1],
+        ValidationError::HeightGap(
+            Level::from_sounding(&snd, 5),
+            Level::from_sounding(&snd, 6),
+            1500.0
+        )
+    );
+}
+
+#[test]
+fn test_mandatory_level_check() {
+    let snd = create_valid_test_sounding();
+    let validator = Validator::new().with_mandatory_level_check(true);
+
+    // The lowest level is 840 hPa.
+    let errs = validator.validate(&snd).unwrap_err().into_inner();
+    assert_eq!(errs, vec![ValidationError::MissingMandatoryLevel(850.0)]);
+
+    let validator = validator.with_mandatory_levels(&[HectoPascal(700.0), HectoPascal(400.0)]);
+    let errs = validator.validate(&snd).unwrap_err().into_inner();
+    assert_eq!(errs, vec![ValidationError::MissingMandatoryLevel(400.0)]);
+
+    let validator = validator.with_mandatory_levels_spanned(true);
+    assert!(validator.validate(&snd).is_ok());
+
+    // A level without a temperature does not count.
+    let mut temperature = snd.temperature_profile()[1..].to_vec();
+    temperature[2] = Optioned::default();
+    let snd = snd.with_temperature_profile(temperature);
+    let errs = validator
+        .clone()
+        .with_mandatory_levels_spanned(false)
+        .validate(&snd)
+        .unwrap_err()
+        .into_inner();
+    assert_eq!(
+        errs,
+        vec![
+            ValidationError::MissingMandatoryLevel(700.0),
+            ValidationError::MissingMandatoryLevel(400.0)
+        ]
+    );
+    assert!(validator.validate(&snd).is_ok());
+}
+
+#[test]
+fn test_sounding_top_check() {
+    let snd = create_valid_test_sounding();
+    let validator = Validator::new().with_sounding_top_check(true);
+    assert!(validator.validate(&snd).is_ok());
+
+    // The balloon burst above 700 hPa.
+    let temperature: Vec<_> = snd.temperature_profile()[1..]
+        .iter()
+        .enumerate()
+        .map(|(i, &t)| if i < 3 { t } else { Optioned::default() })
+        .collect();
+    let burst = snd.clone().with_temperature_profile(temperature);
+    let errs = validator.validate(&burst).unwrap_err().into_inner();
+    assert_eq!(
+        errs,
+        vec![ValidationError::EarlyTermination(Some(
+            Level::from_sounding(&burst, 3)
+        ))]
+    );
+    assert!(validator
+        .clone()
+        .with_sounding_top_pressure(HectoPascal(700.0))
+        .validate(&burst)
+        .is_ok());
+
+    let validator = validator.with_sounding_top_height(Meters(10_000.0));
+    let errs = validator.validate(&snd).unwrap_err().into_inner();
+    assert_eq!(
+        errs,
+        vec![ValidationError::EarlyTermination(Some(
+            Level::from_sounding(&snd, 8)
+        ))]
+    );
+
+    let snd = snd.with_temperature_profile(vec![]);
+    let errs = validator.validate(&snd).unwrap_err().into_inner();
+    assert_eq!(errs, vec![ValidationError::EarlyTermination(None)]);
+}
+
+#[test]
+fn test_missing_data_check() {
+    let snd = create_valid_test_sounding();
+    let validator = Validator::new().with_missing_data_threshold(MissingDataThreshold::new(
+        Profile::DewPoint,
+        (HectoPascal(1100.0), HectoPascal(500.0)),
+        0.3,
+        HectoPascal(100.0),
+    ));
+    assert!(validator.validate(&snd).is_ok());
+
+    let stats = missing_data_stats(
+        &snd,
+        Profile::DewPoint,
+        HectoPascal(1100.0),
+        HectoPascal(500.0),
+    );
+    assert_eq!(
+        stats,
+        Some(MissingDataStats {
+            num_levels: 4,
+            fraction_missing: 0.0,
+            longest_gap: HectoPascal(0.0),
+        })
+    );
+    assert_eq!(
+        missing_data_stats(
+            &snd,
+            Profile::DewPoint,
+            HectoPascal(50.0),
+            HectoPascal(10.0)
+        ),
+        None
+    );
+
+    // Remove the dew points at 800 and 700 hPa.
+    let mut dew_point = snd.dew_point_profile()[1..].to_vec();
+    dew_point[1] = Optioned::default();
+    dew_point[2] = Optioned::default();
+    let snd = snd.with_dew_point_profile(dew_point);
+
+    let stats = missing_data_stats(
+        &snd,
+        Profile::DewPoint,
+        HectoPascal(1100.0),
+        HectoPascal(500.0),
+    );
+    assert_eq!(
+        stats,
+        Some(MissingDataStats {
+            num_levels: 4,
+            fraction_missing: 0.5,
+            longest_gap: HectoPascal(340.0),
+        })
+    );
+
+    let errs = validator.validate(&snd).unwrap_err();
+    println!("{}", errs);
+    assert_eq!(
+        errs.into_inner(),
+        vec![
+            ValidationError::TooMuchMissingData("Dew point", 0.5, 0.3),
+            ValidationError::MissingDataGap("Dew point", 340.0, 100.0),
+        ]
+    );
+}
+
+#[test]
+fn test_missing_data_gap_at_layer_edge() {
+    let snd = create_valid_test_sounding();
+
+    // Remove the dew points at 840 and 100 hPa, the lowest and highest levels.
+    let mut dew_point = snd.dew_point_profile()[1..].to_vec();
+    dew_point[0] = Optioned::default();
+    dew_point[7] = Optioned::default();
+    let snd = snd.with_dew_point_profile(dew_point);
+
+    // The gap at the bottom runs from the lowest level to 800 hPa, not from the bottom of the
+    // layer, which is below the ground.
+    let stats = missing_data_stats(
+        &snd,
+        Profile::DewPoint,
+        HectoPascal(1100.0),
+        HectoPascal(500.0),
+    );
+    assert_eq!(
+        stats,
+        Some(MissingDataStats {
+            num_levels: 4,
+            fraction_missing: 0.25,
+            longest_gap: HectoPascal(40.0),
+        })
+    );
+
+    // The gap at the top runs from 200 hPa to the highest level, not to the top of the layer.
+    let stats = missing_data_stats(
+        &snd,
+        Profile::DewPoint,
+        HectoPascal(300.0),
+        HectoPascal(10.0),
+    );
+    assert_eq!(
+        stats,
+        Some(MissingDataStats {
+            num_levels: 4,
+            fraction_missing: 0.25,
+            longest_gap: HectoPascal(100.0),
+        })
+    );
+
+    // With the dew point missing at the lowest level, the sounding passes the example threshold.
+    assert!(Validator::new()
+        .with_missing_data_threshold(MissingDataThreshold::new(
+            Profile::DewPoint,
+            (HectoPascal(1100.0), HectoPascal(500.0)),
+            0.3,
+            HectoPascal(100.0),
+        ))
+        .validate(&snd)
+        .is_ok());
+}
+
+#[test]
+fn test_unit_check() {
+    let suspected_units = |snd: &Sounding| -> Vec<_> {
+        Validator::new()
+            .with_unit_check(true)
+            .run(snd)
+            .into_inner()
+            .into_iter()
+            .filter_map(|err| match err {
+                ValidationError::SuspectedUnitError(name, unit) => Some((name, unit)),
+                _ => None,
+            })
+            .collect()
+    };
+
+    let snd = create_valid_test_sounding();
+    assert!(suspected_units(&snd).is_empty());
+
+    let temperature: Vec<_> = snd.temperature_profile()[1..]
+        .iter()
+        .map(|t| t.map_t(|Celsius(t)| Celsius(t + 273.15)))
+        .collect();
+    let pressure: Vec<_> = snd.pressure_profile()[1..]
+        .iter()
+        .map(|p| p.map_t(|HectoPascal(p)| HectoPascal(p * 100.0)))
+        .collect();
+    let mut wind = snd.wind_profile()[1..].to_vec();
+    wind[6] = Optioned::from(WindSpdDir {
+        direction: 240.0,
+        speed: Knots(300.0),
+    });
+    let snd = snd
+        .with_temperature_profile(temperature)
+        .with_pressure_profile(pressure)
+        .with_mslp(HectoPascal(101_400.0))
+        .with_wind_profile(wind);
+
+    assert_eq!(
+        suspected_units(&snd),
+        vec![
+            ("Temperature", "K"),
+            ("Pressure", "Pa"),
+            ("MSLP", "Pa"),
+            ("Wind speed", "km/h, or knots converted as if they were m/s")
+        ]
+    );
+
+    // The test sounding stores cloud as a percentage.
+    let check_cloud_scale = |snd: &Sounding, scale| {
+        Validator::new()
+            .with_unit_check(true)
+            .with_cloud_scale(scale)
+            .run(snd)
+            .into_inner()
+    };
+    let snd = create_valid_test_sounding();
+    assert!(check_cloud_scale(&snd, CloudScale::Percent).is_empty());
+    assert!(check_cloud_scale(&snd, CloudScale::Fraction)
+        .contains(&ValidationError::SuspectedUnitError("Cloud fraction", "%")));
+
+    let cloud_fraction: Vec<_> = snd.cloud_fraction_profile()[1..]
+        .iter()
+        .map(|cld| cld.map_t(|cld| cld / 100.0))
+        .collect();
+    let snd = snd.with_cloud_fraction_profile(cloud_fraction);
+    assert!(check_cloud_scale(&snd, CloudScale::Fraction).is_empty());
+    assert_eq!(
+        check_cloud_scale(&snd, CloudScale::Percent),
+        vec![ValidationError::SuspectedUnitError(
+            "Cloud fraction",
+            "fraction"
+        )]
+    );
+}
+
+#[test]
+fn test_wind_consistency_check() {
+    let snd = create_valid_test_sounding();
+    let validator = Validator::new().with_wind_consistency_check(true);
+
+    let errs = validator.validate(&snd).unwrap_err();
+    println!("{}", errs);
+    let errs = errs.into_inner();
+    assert_eq!(errs.len(), 3);
+    assert_eq!(
+        errs[0],
+        ValidationError::WindSpeedWithoutDirection(Some(Level::from_sounding(&snd, 1)), 5.0)
+    );
+    let shears: Vec<_> = errs[1..]
+        .iter()
+        .map(|err| match err {
+            ValidationError::ExcessiveWindShear(bottom, top, shear) => {
+                (bottom.index, top.index, shear.round())
+            }
+            err => panic!("Error is of wrong type: {}", err),
+        })
+        .collect();
+    assert_eq!(shears, vec![(6, 7, 80.0), (7, 8, 51.0)]);
+
+    // North as zero, a calm wind with a direction, and a speed spike.
+    let mut wind = snd.wind_profile()[1..].to_vec();
+    wind[1] = Optioned::from(WindSpdDir {
+        direction: 360.0,
+        speed: Knots(10.0),
+    });
+    wind[2] = Optioned::from(WindSpdDir {
+        direction: 80.0,
+        speed: Knots(0.0),
+    });
+    wind[3] = Optioned::from(WindSpdDir {
+        direction: 120.0,
+        speed: Knots(60.0),
+    });
+    let snd = snd.with_wind_profile(wind);
+    let errs = validator
+        .with_wind_north_as_zero(true)
+        .with_max_wind_shear_per_km(Knots(100.0))
+        .validate(&snd)
+        .unwrap_err();
+    println!("{}", errs);
+    assert_eq!(
+        errs.into_inner(),
+        vec![
+            ValidationError::UnnormalizedWindDirection(Some(Level::from_sounding(&snd, 2)), 360.0),
+            ValidationError::CalmWindWithDirection(Some(Level::from_sounding(&snd, 3)), 80.0),
+            ValidationError::WindSpeedSpike(Level::from_sounding(&snd, 4), 60.0, 0.0, 27.0),
+        ]
+    );
+
+    // After normalizing, 360 is no longer reported.
+    let snd = normalize_north_winds(snd);
+    assert_eq!(snd.wind_profile()[2].into_option().unwrap().direction, 0.0);
+    let errs = Validator::new()
+        .with_wind_consistency_check(true)
+        .with_wind_north_as_zero(true)
+        .with_max_wind_shear_per_km(Knots(100.0))
+        .validate(&snd)
+        .unwrap_err()
+        .into_inner();
+    assert_eq!(errs.len(), 2);
+    assert!(!errs
+        .iter()
+        .any(|err| matches!(err, ValidationError::UnnormalizedWindDirection(..))));
+}
+
+#[test]
+fn test_wind_shear_min_depth() {
+    // Levels 10 m apart with speeds that alternate by 1 knot, like high resolution data.
+    let num_levels = 20;
+    let snd = Sounding::new()
+        .with_pressure_profile(
+            (0..num_levels)
+                .map(|i| Optioned::from(HectoPascal(900.0 - i as f64)))
+                .collect(),
+        )
+        .with_height_profile(
+            (0..num_levels)
+                .map(|i| Optioned::from(Meters(1000.0 + 10.0 * i as f64)))
+                .collect(),
+        )
+        .with_wind_profile(
+            (0..num_levels)
+                .map(|i| {
+                    Optioned::from(WindSpdDir {
+                        direction: 270.0,
+                        speed: Knots(20.0 + (i % 2) as f64),
+                    })
+                })
+                .collect(),
+        );
+
+    let num_shears = |validator: Validator| {
+        validator
+            .with_wind_consistency_check(true)
+            .run(&snd)
+            .into_inner()
+            .into_iter()
+            .filter(|err| matches!(err, ValidationError::ExcessiveWindShear(..)))
+            .count()
+    };
+    assert_eq!(num_shears(Validator::new()), 0);
+    assert_eq!(
+        num_shears(Validator::new().with_wind_shear_min_depth(Meters(0.0))),
+        num_levels - 1
+    );
+}
+
+#[test]
+fn test_surface_wind_check() {
+    let snd = create_valid_test_sounding();
+    let validator = Validator::new().with_surface_wind_check(true);
+    assert!(validator.validate(&snd).is_ok());
+
+    // Looks like a report from another station.
+    let snd = snd.with_sfc_wind(WindSpdDir {
+        direction: 180.0,
+        speed: Knots(40.0),
+    });
+    let errs = validator.validate(&snd).unwrap_err();
+    println!("{}", errs);
+    assert_eq!(
+        errs.into_inner(),
+        vec![
+            ValidationError::SurfaceProfileMismatch(
+                "Wind speed",
+                Level::from_sounding(&snd, 1),
+                40.0,
+                5.0
+            ),
+            ValidationError::SurfaceProfileMismatch(
+                "Wind direction",
+                Level::from_sounding(&snd, 1),
+                180.0,
+                0.0
+            ),
+        ]
+    );
+
+    // The speed tolerance does not decide which directions are compared.
+    let errs = Validator::new()
+        .with_surface_wind_check(true)
+        .with_surface_wind_speed_tolerance(Knots(50.0))
+        .validate(&snd)
+        .unwrap_err()
+        .into_inner();
+    assert_eq!(
+        errs,
+        vec![ValidationError::SurfaceProfileMismatch(
+            "Wind direction",
+            Level::from_sounding(&snd, 1),
+            180.0,
+            0.0
+        )]
+    );
+
+    // Too light for the direction to be compared.
+    let errs = Validator::new()
+        .with_surface_wind_check(true)
+        .with_surface_wind_direction_min_speed(Knots(50.0))
+        .validate(&snd)
+        .unwrap_err()
+        .into_inner();
+    assert_eq!(
+        errs,
+        vec![ValidationError::SurfaceProfileMismatch(
+            "Wind speed",
+            Level::from_sounding(&snd, 1),
+            40.0,
+            5.0
+        )]
+    );
+
+    // Looks like m/s stored as knots, or knots converted as if they were m/s.
+    let mut wind = snd.wind_profile()[1..].to_vec();
+    for (w, spd) in wind.iter_mut().zip([15.0, 16.0, 17.0].iter()) {
+        *w = Optioned::from(WindSpdDir {
+            direction: 270.0,
+            speed: Knots(*spd),
+        });
+    }
+    let snd_ratio = snd
+        .clone()
+        .with_wind_profile(wind)
+        .with_sfc_wind(WindSpdDir {
+            direction: 260.0,
+            speed: Knots(8.0),
+        });
+    assert_eq!(
+        validator.validate(&snd_ratio).unwrap_err().into_inner(),
+        vec![ValidationError::SuspectedUnitError(
+            "Surface wind speed",
+            "m/s"
+        )]
+    );
+    let snd_ratio = snd_ratio.with_sfc_wind(WindSpdDir {
+        direction: 260.0,
+        speed: Knots(31.0),
+    });
+    assert_eq!(
+        validator.validate(&snd_ratio).unwrap_err().into_inner(),
+        vec![ValidationError::SuspectedUnitError(
+            "Surface wind speed",
+            "knots converted as if they were m/s"
+        )]
+    );
+    let snd_ratio = snd_ratio.with_sfc_wind(WindSpdDir {
+        direction: 260.0,
+        speed: Knots(14.0),
+    });
+    assert!(validator.validate(&snd_ratio).is_ok());
+
+    // Agrees with the third level.
+    let snd = snd.with_sfc_wind(WindSpdDir {
+        direction: 90.0,
+        speed: Knots(25.0),
+    });
+    assert!(validator.validate(&snd).is_ok());
+
+    let errs = validator
+        .with_surface_wind_levels(1)
+        .validate(&snd)
+        .unwrap_err()
+        .into_inner();
+    assert_eq!(
+        errs,
+        vec![ValidationError::SurfaceProfileMismatch(
+            "Wind speed",
+            Level::from_sounding(&snd, 1),
+            25.0,
+            5.0
+        )]
+    );
+}
+
+#[test]
+fn test_thermo_checks_with_infinite_values() {
+    // The infinite pressure made the wet bulb calculation panic in debug builds.
+    let snd = create_valid_test_sounding();
+    let mut pressure = snd.pressure_profile()[1..].to_vec();
+    pressure[7] = Optioned::from(HectoPascal(f64::INFINITY));
+    let mut temperature = snd.temperature_profile()[1..].to_vec();
+    temperature[6] = Optioned::from(Celsius(f64::INFINITY));
+    temperature[7] = Optioned::from(Celsius(-107.5));
+    let mut dew_point = snd.dew_point_profile()[1..].to_vec();
+    dew_point[7] = Optioned::from(Celsius(-90.9));
+    let snd = snd
+        .with_pressure_profile(pressure)
+        .with_temperature_profile(temperature)
+        .with_dew_point_profile(dew_point);
+
+    let errs = Validator::new()
+        .with_hydrostatic_check(true)
+        .with_theta_e_check(true)
+        .with_wet_bulb_check(true)
+        .with_superadiabatic_check(true)
+        .run(&snd);
+    assert!(errs
+        .iter()
+        .any(|(_, err)| matches!(err, ValidationError::NonFiniteValue("Pressure", ..))));
+}
+
+#[test]
+fn test_station_elevation_check() {
+    let validator = Validator::new().with_station_elevation_check(true);
+
+    // The lowest level is 27 m above the station.
+    let snd = create_valid_test_sounding();
+    assert!(validator.validate(&snd).is_ok());
+
+    // Metadata from a station near sea level.
+    let snd = snd.with_station_info(StationInfo::new_with_values(
+        1,
+        (45.0, -115.0),
+        Meters(10.0),
+    ));
+    let errs = validator.validate(&snd).unwrap_err().into_inner();
+    assert_eq!(
+        errs,
+        vec![ValidationError::StationElevationMismatch(
+            Level::from_sounding(&snd, 1),
+            10.0,
+            1050.0
+        )]
+    );
+
+    assert!(validator
+        .with_station_elevation_tolerance(Meters(1500.0))
+        .validate(&snd)
+        .is_ok());
+}